@@ -0,0 +1,77 @@
+use std::fs;
+use std::io::{self, Read, Write};
+
+const USAGE: &str = "Usage: eldiro fmt [--check] [FILE]...";
+
+// Formats the given files in place, or standard input to standard output if
+// there are none. With `--check` nothing is written, and files that aren't
+// formatted are listed instead. Returns whether everything went well, which
+// for `--check` means that everything was formatted already.
+pub(crate) fn run(args: &[String]) -> io::Result<bool> {
+    let mut check = false;
+    let mut paths = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option '{}'\n{}", arg, USAGE);
+                return Ok(false);
+            }
+            _ => paths.push(arg.as_str()),
+        }
+    }
+
+    if paths.is_empty() {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+
+        return match format("<stdin>", &input) {
+            Some(formatted) if check => Ok(formatted == input),
+            Some(formatted) => {
+                io::stdout().write_all(formatted.as_bytes())?;
+                Ok(true)
+            }
+            None => Ok(false),
+        };
+    }
+
+    let mut success = true;
+    for path in paths {
+        let input = fs::read_to_string(path)?;
+        let formatted = match format(path, &input) {
+            Some(formatted) => formatted,
+            None => {
+                success = false;
+                continue;
+            }
+        };
+
+        if formatted == input {
+            continue;
+        }
+
+        if check {
+            println!("{} is not formatted", path);
+            success = false;
+        } else {
+            fs::write(path, formatted)?;
+        }
+    }
+
+    Ok(success)
+}
+
+fn format(path: &str, input: &str) -> Option<String> {
+    match eldiro::format(input) {
+        Ok(formatted) => Some(formatted),
+        Err(errors) => {
+            for error in errors {
+                let line = input[..error.span().start].matches('\n').count() + 1;
+                eprintln!("{}:{}: Parse error: {}", path, line, error);
+            }
+
+            None
+        }
+    }
+}
@@ -1,6 +1,32 @@
+mod fmt;
+
+use std::env;
 use std::io::{self, Write};
+use std::process;
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.split_first() {
+        None => repl(),
+        Some((command, args)) if command == "fmt" => {
+            if !fmt::run(args)? {
+                process::exit(1);
+            }
+
+            Ok(())
+        }
+        Some((command, _)) => {
+            eprintln!(
+                "Unknown command '{}'\nUsage: eldiro [fmt [--check] [FILE]...]",
+                command
+            );
+            process::exit(2);
+        }
+    }
+}
+
+fn repl() -> io::Result<()> {
     let stdin = io::stdin();
     let mut stdout = io::stdout();
     let mut stderr = io::stderr();
@@ -24,7 +50,11 @@ fn main() -> io::Result<()> {
 }
 
 fn run(input: &str, env: &mut eldiro::Env) -> Result<Option<eldiro::Val>, String> {
-    let parse = eldiro::parse(input).map_err(|msg| format!("Parse error: {}", msg))?;
+    let parse = eldiro::parse(input);
+    if !parse.errors().is_empty() {
+        let msgs: Vec<_> = parse.errors().iter().map(|err| err.to_string()).collect();
+        return Err(format!("Parse error: {}", msgs.join(", ")));
+    }
 
     let evaluated = parse
         .eval(env)
@@ -32,6 +62,6 @@ fn run(input: &str, env: &mut eldiro::Env) -> Result<Option<eldiro::Val>, String
 
     match evaluated {
         eldiro::Val::Unit => Ok(None),
-        eldiro::Val::Number(n) => Ok(Some(evaluated)),
+        _ => Ok(Some(evaluated)),
     }
 }
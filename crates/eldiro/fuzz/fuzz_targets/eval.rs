@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let parse = eldiro::parse(s);
+    if parse.errors().is_empty() {
+        let mut env = eldiro::Env::with_limits(eldiro::Limits {
+            fuel: Some(100_000),
+            ..eldiro::Limits::default()
+        });
+        let _ = parse.eval(&mut env);
+    }
+});
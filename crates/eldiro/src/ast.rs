@@ -0,0 +1,578 @@
+// Typed views of the syntax tree. Accessors return `None` when the part they
+// look for is missing, which happens when the input has syntax errors.
+
+use crate::expr::Op;
+use crate::lexer::TokenKind;
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+macro_rules! ast_node {
+    ($name:ident, $kind:ident) => {
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name<'a>(&'a SyntaxNode);
+
+        impl<'a> $name<'a> {
+            pub fn cast(node: &'a SyntaxNode) -> Option<Self> {
+                if node.kind() == SyntaxKind::$kind {
+                    Some(Self(node))
+                } else {
+                    None
+                }
+            }
+
+            pub fn syntax(&self) -> &'a SyntaxNode {
+                self.0
+            }
+        }
+    };
+}
+
+ast_node!(Program, Program);
+ast_node!(BindingDef, BindingDef);
+ast_node!(FuncDef, FuncDef);
+ast_node!(Assignment, Assignment);
+ast_node!(Number, Number);
+ast_node!(BindingUsage, BindingUsage);
+ast_node!(Operation, Operation);
+ast_node!(Unary, Unary);
+ast_node!(Range, Range);
+ast_node!(Pipe, Pipe);
+ast_node!(FuncCall, FuncCall);
+ast_node!(Paren, Paren);
+ast_node!(List, List);
+ast_node!(Block, Block);
+ast_node!(If, If);
+ast_node!(While, While);
+ast_node!(For, For);
+ast_node!(Break, Break);
+ast_node!(Continue, Continue);
+ast_node!(Return, Return);
+ast_node!(Propagate, Propagate);
+ast_node!(TryCatch, TryCatch);
+ast_node!(Catch, Catch);
+
+/// An `Ok` or `Err` expression.
+#[derive(Debug, Clone, Copy)]
+pub struct ResultExpr<'a>(&'a SyntaxNode);
+
+impl<'a> ResultExpr<'a> {
+    pub fn cast(node: &'a SyntaxNode) -> Option<Self> {
+        match node.kind() {
+            SyntaxKind::Ok | SyntaxKind::Err => Some(Self(node)),
+            _ => None,
+        }
+    }
+
+    pub fn syntax(&self) -> &'a SyntaxNode {
+        self.0
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.0.kind() == SyntaxKind::Ok
+    }
+
+    pub fn value(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Stmt<'a> {
+    BindingDef(BindingDef<'a>),
+    FuncDef(FuncDef<'a>),
+    Expr(Expr<'a>),
+}
+
+impl<'a> Stmt<'a> {
+    pub fn cast(node: &'a SyntaxNode) -> Option<Self> {
+        match node.kind() {
+            SyntaxKind::BindingDef => Some(Self::BindingDef(BindingDef(node))),
+            SyntaxKind::FuncDef => Some(Self::FuncDef(FuncDef(node))),
+            _ => Expr::cast(node).map(Self::Expr),
+        }
+    }
+
+    pub fn syntax(&self) -> &'a SyntaxNode {
+        match self {
+            Self::BindingDef(binding_def) => binding_def.syntax(),
+            Self::FuncDef(func_def) => func_def.syntax(),
+            Self::Expr(expr) => expr.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Expr<'a> {
+    Assignment(Assignment<'a>),
+    Number(Number<'a>),
+    BindingUsage(BindingUsage<'a>),
+    Operation(Operation<'a>),
+    Unary(Unary<'a>),
+    Range(Range<'a>),
+    Pipe(Pipe<'a>),
+    FuncCall(FuncCall<'a>),
+    Paren(Paren<'a>),
+    List(List<'a>),
+    Block(Block<'a>),
+    If(If<'a>),
+    While(While<'a>),
+    For(For<'a>),
+    Break(Break<'a>),
+    Continue(Continue<'a>),
+    Return(Return<'a>),
+    Result(ResultExpr<'a>),
+    Propagate(Propagate<'a>),
+    TryCatch(TryCatch<'a>),
+}
+
+impl<'a> Expr<'a> {
+    pub fn cast(node: &'a SyntaxNode) -> Option<Self> {
+        let expr = match node.kind() {
+            SyntaxKind::Assignment => Self::Assignment(Assignment(node)),
+            SyntaxKind::Number => Self::Number(Number(node)),
+            SyntaxKind::BindingUsage => Self::BindingUsage(BindingUsage(node)),
+            SyntaxKind::Operation => Self::Operation(Operation(node)),
+            SyntaxKind::Unary => Self::Unary(Unary(node)),
+            SyntaxKind::Range => Self::Range(Range(node)),
+            SyntaxKind::Pipe => Self::Pipe(Pipe(node)),
+            SyntaxKind::FuncCall => Self::FuncCall(FuncCall(node)),
+            SyntaxKind::Paren => Self::Paren(Paren(node)),
+            SyntaxKind::List => Self::List(List(node)),
+            SyntaxKind::Block => Self::Block(Block(node)),
+            SyntaxKind::If => Self::If(If(node)),
+            SyntaxKind::While => Self::While(While(node)),
+            SyntaxKind::For => Self::For(For(node)),
+            SyntaxKind::Break => Self::Break(Break(node)),
+            SyntaxKind::Continue => Self::Continue(Continue(node)),
+            SyntaxKind::Return => Self::Return(Return(node)),
+            SyntaxKind::Ok | SyntaxKind::Err => Self::Result(ResultExpr(node)),
+            SyntaxKind::Propagate => Self::Propagate(Propagate(node)),
+            SyntaxKind::TryCatch => Self::TryCatch(TryCatch(node)),
+            SyntaxKind::Program
+            | SyntaxKind::BindingDef
+            | SyntaxKind::FuncDef
+            | SyntaxKind::Catch
+            | SyntaxKind::Error => return None,
+        };
+
+        Some(expr)
+    }
+
+    pub fn syntax(&self) -> &'a SyntaxNode {
+        match self {
+            Self::Assignment(node) => node.syntax(),
+            Self::Number(node) => node.syntax(),
+            Self::BindingUsage(node) => node.syntax(),
+            Self::Operation(node) => node.syntax(),
+            Self::Unary(node) => node.syntax(),
+            Self::Range(node) => node.syntax(),
+            Self::Pipe(node) => node.syntax(),
+            Self::FuncCall(node) => node.syntax(),
+            Self::Paren(node) => node.syntax(),
+            Self::List(node) => node.syntax(),
+            Self::Block(node) => node.syntax(),
+            Self::If(node) => node.syntax(),
+            Self::While(node) => node.syntax(),
+            Self::For(node) => node.syntax(),
+            Self::Break(node) => node.syntax(),
+            Self::Continue(node) => node.syntax(),
+            Self::Return(node) => node.syntax(),
+            Self::Result(node) => node.syntax(),
+            Self::Propagate(node) => node.syntax(),
+            Self::TryCatch(node) => node.syntax(),
+        }
+    }
+}
+
+fn exprs(node: &SyntaxNode) -> impl Iterator<Item = Expr<'_>> {
+    node.child_nodes().filter_map(Expr::cast)
+}
+
+fn nth_expr(node: &SyntaxNode, n: usize) -> Option<Expr<'_>> {
+    exprs(node).nth(n)
+}
+
+fn stmts(node: &SyntaxNode) -> impl Iterator<Item = Stmt<'_>> {
+    node.child_nodes().filter_map(Stmt::cast)
+}
+
+fn blocks(node: &SyntaxNode) -> impl Iterator<Item = Block<'_>> {
+    node.child_nodes().filter_map(Block::cast)
+}
+
+fn tokens(node: &SyntaxNode, kind: TokenKind) -> impl Iterator<Item = &SyntaxToken> {
+    node.child_tokens()
+        .filter(move |token| token.kind() == kind)
+}
+
+// Pairs each child node with the keyword right before it, if there is one.
+fn keyword_nodes(node: &SyntaxNode) -> impl Iterator<Item = (Option<&str>, &SyntaxNode)> {
+    let mut keyword = None;
+
+    node.children().iter().filter_map(move |child| match child {
+        SyntaxElement::Token(token) => {
+            if token.kind() == TokenKind::Keyword {
+                keyword = Some(token.text());
+            }
+            None
+        }
+        SyntaxElement::Node(node) => Some((keyword.take(), node)),
+    })
+}
+
+fn ident(node: &SyntaxNode) -> Option<&str> {
+    tokens(node, TokenKind::Ident).next().map(SyntaxToken::text)
+}
+
+impl<'a> Program<'a> {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt<'a>> {
+        stmts(self.0)
+    }
+}
+
+impl<'a> BindingDef<'a> {
+    pub fn is_mutable(&self) -> bool {
+        tokens(self.0, TokenKind::Keyword).any(|token| token.text() == "mut")
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        ident(self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> FuncDef<'a> {
+    pub fn name(&self) -> Option<&'a str> {
+        ident(self.0)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = &'a str> {
+        tokens(self.0, TokenKind::Ident)
+            .skip(1)
+            .map(SyntaxToken::text)
+    }
+
+    pub fn body(&self) -> Option<Stmt<'a>> {
+        stmts(self.0).next()
+    }
+}
+
+impl<'a> Assignment<'a> {
+    pub fn name(&self) -> Option<&'a str> {
+        ident(self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> Number<'a> {
+    /// The literal as written, including the sign of a negative literal.
+    pub fn text(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl<'a> BindingUsage<'a> {
+    pub fn name(&self) -> Option<&'a str> {
+        self.0.child_tokens().next().map(SyntaxToken::text)
+    }
+}
+
+impl<'a> Operation<'a> {
+    pub fn lhs(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+
+    pub fn op(&self) -> Option<Op> {
+        tokens(self.0, TokenKind::Punct).find_map(|token| Op::from_symbol(token.text()))
+    }
+
+    pub fn rhs(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 1)
+    }
+}
+
+impl<'a> Unary<'a> {
+    /// The `-`, `!` or `~` token.
+    pub fn op(&self) -> Option<&'a SyntaxToken> {
+        tokens(self.0, TokenKind::Punct).next()
+    }
+
+    pub fn operand(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> Range<'a> {
+    pub fn start(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+
+    pub fn end(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 1)
+    }
+}
+
+impl<'a> Pipe<'a> {
+    pub fn arg(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+
+    // The argument can be a call as well, so the call is the last child.
+    pub fn call(&self) -> Option<FuncCall<'a>> {
+        self.0.child_nodes().last().and_then(FuncCall::cast)
+    }
+}
+
+impl<'a> FuncCall<'a> {
+    pub fn callee(&self) -> Option<&'a str> {
+        ident(self.0)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr<'a>> {
+        exprs(self.0)
+    }
+}
+
+impl<'a> Paren<'a> {
+    pub fn expr(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> List<'a> {
+    pub fn items(&self) -> impl Iterator<Item = Expr<'a>> {
+        exprs(self.0)
+    }
+}
+
+impl<'a> Block<'a> {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt<'a>> {
+        stmts(self.0)
+    }
+}
+
+impl<'a> If<'a> {
+    /// The condition and block of the `if` and of each `else if` after it.
+    pub fn branches(&self) -> impl Iterator<Item = (Expr<'a>, Block<'a>)> {
+        let nodes: Vec<_> = keyword_nodes(self.0).collect();
+        let branches: Vec<_> = nodes
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [(Some("if"), cond), (None, block)] => {
+                    Some((Expr::cast(cond)?, Block::cast(block)?))
+                }
+                _ => None,
+            })
+            .collect();
+
+        branches.into_iter()
+    }
+
+    pub fn else_branch(&self) -> Option<Block<'a>> {
+        keyword_nodes(self.0).find_map(|(keyword, node)| match keyword {
+            Some("else") => Block::cast(node),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> While<'a> {
+    pub fn cond(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+
+    pub fn body(&self) -> Option<Block<'a>> {
+        self.0.child_nodes().nth(1).and_then(Block::cast)
+    }
+}
+
+impl<'a> For<'a> {
+    pub fn binding(&self) -> Option<&'a str> {
+        ident(self.0)
+    }
+
+    pub fn iterable(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+
+    pub fn body(&self) -> Option<Block<'a>> {
+        self.0.child_nodes().nth(1).and_then(Block::cast)
+    }
+}
+
+impl<'a> Break<'a> {
+    pub fn value(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> Return<'a> {
+    pub fn value(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> Propagate<'a> {
+    pub fn expr(&self) -> Option<Expr<'a>> {
+        nth_expr(self.0, 0)
+    }
+}
+
+impl<'a> TryCatch<'a> {
+    pub fn body(&self) -> Option<Block<'a>> {
+        blocks(self.0).next()
+    }
+
+    pub fn catches(&self) -> impl Iterator<Item = Catch<'a>> {
+        self.0.child_nodes().filter_map(Catch::cast)
+    }
+}
+
+impl<'a> Catch<'a> {
+    /// The kind of error this clause catches, or `None` if it catches any
+    /// error.
+    pub fn kind(&self) -> Option<&'a str> {
+        ident(self.0).filter(|name| name.starts_with(|c: char| c.is_ascii_uppercase()))
+    }
+
+    /// The name the error is bound to, if this clause catches any error.
+    pub fn error_name(&self) -> Option<&'a str> {
+        ident(self.0).filter(|_| self.kind().is_none())
+    }
+
+    /// The names the fields of the error are bound to, if this clause
+    /// catches one kind of error.
+    pub fn fields(&self) -> impl Iterator<Item = &'a str> {
+        tokens(self.0, TokenKind::Ident)
+            .skip(1)
+            .map(SyntaxToken::text)
+    }
+
+    pub fn handler(&self) -> Option<Block<'a>> {
+        blocks(self.0).next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn access_binding_def() {
+        let parse = parse("let mut x = 1 + 2");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::BindingDef(binding_def)) => {
+                assert!(binding_def.is_mutable());
+                assert_eq!(binding_def.name(), Some("x"));
+
+                match binding_def.value() {
+                    Some(Expr::Operation(operation)) => {
+                        assert_eq!(operation.op(), Some(Op::Add));
+                        assert_eq!(operation.rhs().unwrap().syntax().to_string(), "2");
+                    }
+                    value => panic!("unexpected value {:?}", value),
+                }
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn access_func_def() {
+        let parse = parse("fn add a b => { a + b }");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::FuncDef(func_def)) => {
+                assert_eq!(func_def.name(), Some("add"));
+                assert_eq!(func_def.params().collect::<Vec<_>>(), vec!["a", "b"]);
+                assert!(matches!(func_def.body(), Some(Stmt::Expr(Expr::Block(_)))));
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn access_pipe_into_call() {
+        let parse = parse("f 1 |> g 2");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::Expr(Expr::Pipe(pipe))) => {
+                assert!(matches!(pipe.arg(), Some(Expr::FuncCall(_))));
+
+                let call = pipe.call().unwrap();
+                assert_eq!(call.callee(), Some("g"));
+                assert_eq!(call.args().count(), 1);
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn access_else_if_chain() {
+        let parse = parse("if a { 1 } else if { b } { 2 } else { 3 }");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::Expr(Expr::If(if_else))) => {
+                let conds: Vec<_> = if_else
+                    .branches()
+                    .map(|(cond, _)| cond.syntax().to_string())
+                    .collect();
+                assert_eq!(conds, vec!["a", "{ b }"]);
+
+                let else_branch = if_else.else_branch().unwrap();
+                assert_eq!(else_branch.syntax().to_string(), "{ 3 }");
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn access_catches() {
+        let parse = parse("try { a } catch Overflow lhs rhs { 1 } catch e { 2 }");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::Expr(Expr::TryCatch(try_catch))) => {
+                assert_eq!(try_catch.body().unwrap().syntax().to_string(), "{ a }");
+
+                let catches: Vec<_> = try_catch.catches().collect();
+                assert_eq!(catches.len(), 2);
+
+                assert_eq!(catches[0].kind(), Some("Overflow"));
+                assert_eq!(catches[0].error_name(), None);
+                assert_eq!(catches[0].fields().collect::<Vec<_>>(), vec!["lhs", "rhs"]);
+
+                assert_eq!(catches[1].kind(), None);
+                assert_eq!(catches[1].error_name(), Some("e"));
+                assert_eq!(catches[1].fields().count(), 0);
+                assert_eq!(catches[1].handler().unwrap().syntax().to_string(), "{ 2 }");
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn missing_parts_are_none() {
+        let parse = parse("let x =");
+        let stmt = parse.ast().stmts().next();
+
+        match stmt {
+            Some(Stmt::BindingDef(binding_def)) => {
+                assert_eq!(binding_def.name(), Some("x"));
+                assert!(binding_def.value().is_none());
+            }
+            stmt => panic!("unexpected statement {:?}", stmt),
+        }
+    }
+}
@@ -1,32 +1,42 @@
 use crate::env::Env;
-use crate::{expr::Expr, utils};
+use crate::expr::Expr;
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct BindingDef {
     pub name: String,
     pub val: Expr,
+    pub mutable: bool,
 }
 
-impl BindingDef {
-    pub fn new(s: &str) -> Result<(&str, Self), String> {
-        let s = utils::tag("let", s)?;
-        let (s, _) = utils::extract_whitespace1(s)?;
+impl Parsable for BindingDef {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::BindingDef, |p| {
+            p.expect_keyword("let")?;
+            let mutable = p.eat_keyword("mut");
 
-        let (s, name) = utils::extract_ident(s)?;
-        let (s, _) = utils::extract_whitespace(s);
+            let name = p.expect_ident()?.to_owned();
+            p.expect_punct("=")?;
 
-        let s = utils::tag("=", s)?;
-        let (s, _) = utils::extract_whitespace(s);
+            let val = Expr::parse(p)?;
 
-        let (s, val) = Expr::new(s)?;
+            Ok(Self { name, val, mutable })
+        })
+    }
+}
 
-        let name = name.to_owned();
-        let binding_def = Self { name, val };
+impl BindingDef {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<(), Unwind> {
+        let val = self.val.eval(env)?;
 
-        Ok((s, binding_def))
-    }
+        if self.mutable {
+            env.store_mutable_binding(self.name.clone(), val)?;
+        } else {
+            env.store_binding(self.name.clone(), val)?;
+        }
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<(), String> {
-        env.store_binding(self.name.clone(), self.val.eval(env)?);
         Ok(())
     }
 }
@@ -48,16 +58,70 @@ mod tests {
                         lhs: Box::new(Expr::Number(Number(10))),
                         rhs: Box::new(Expr::Number(Number(2))),
                         op: Op::Div
-                    }
+                    },
+                    mutable: false,
                 }
             ))
         );
     }
+
+    #[test]
+    fn parse_mutable_binding_def() {
+        assert_eq!(
+            BindingDef::new("let mut a = 1"),
+            Ok((
+                "",
+                BindingDef {
+                    name: "a".to_owned(),
+                    val: Expr::Number(Number(1)),
+                    mutable: true,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_binding_def_with_name_starting_with_mut() {
+        assert_eq!(
+            BindingDef::new("let mutant = 1"),
+            Ok((
+                "",
+                BindingDef {
+                    name: "mutant".to_owned(),
+                    val: Expr::Number(Number(1)),
+                    mutable: false,
+                }
+            ))
+        );
+    }
+    #[test]
+    fn parse_binding_def_with_unicode_name() {
+        assert_eq!(
+            BindingDef::new("let größe_1 = 1"),
+            Ok((
+                "",
+                BindingDef {
+                    name: "größe_1".to_owned(),
+                    val: Expr::Number(Number(1)),
+                    mutable: false,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn cannot_parse_binding_def_named_after_keyword() {
+        assert_eq!(
+            BindingDef::new("let let = 1"),
+            Err("keyword 'let' cannot be used as an identifier".to_owned())
+        );
+    }
+
     #[test]
     fn cannot_parse_binding_def_without_space_after_let() {
         assert_eq!(
             BindingDef::new("letaaa=1+2"),
-            Err("expected whitespace".to_owned())
+            Err("expected `let`".to_owned())
         );
     }
 }
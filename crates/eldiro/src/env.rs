@@ -1,68 +1,378 @@
+use crate::error::EvalError;
+use crate::limits::{Context, DepthGuard, HeapCharge, Limits};
 use crate::stmt::Stmt;
-use crate::val::Val;
-use std::collections::HashMap;
+use crate::val::{Func, Val};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::rc::Rc;
 
 #[derive(Debug, PartialEq, Default)]
 pub struct Env<'parent> {
-    named: HashMap<String, NamedInfo>,
+    id: usize,
+    named: RefCell<HashMap<String, NamedInfo>>,
     parent: Option<&'parent Self>,
+    // The environment a call frame was created from, which is still around
+    // for as long as the call is.
+    caller: Option<&'parent Self>,
+    context: Rc<Context>,
+    call_frame: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 enum NamedInfo {
-    Binding(Val),
-    Func { params: Vec<String>, body: Stmt },
+    Binding {
+        val: Val,
+        mutable: bool,
+    },
+    Func {
+        params: Rc<[String]>,
+        body: Rc<Stmt>,
+    },
 }
 
 impl NamedInfo {
-    fn into_binding(self) -> Option<Val> {
-        if let Self::Binding(val) = self {
-            Some(val)
-        } else {
-            None
-        }
-    }
+    fn heap_size(&self, name: &str) -> usize {
+        let own_size = match self {
+            Self::Binding { val, .. } => val.unshared_heap_size(),
+            Self::Func { params, .. } => params
+                .iter()
+                .map(|param| mem::size_of::<String>() + param.len())
+                .sum(),
+        };
 
-    fn into_func(self) -> Option<(Vec<String>, Stmt)> {
-        if let Self::Func { params, body } = self {
-            Some((params, body))
-        } else {
-            None
-        }
+        name.len() + mem::size_of::<Self>() + own_size
     }
 }
 
 impl<'parent> Env<'parent> {
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            id: 0,
+            named: RefCell::default(),
+            parent: None,
+            caller: None,
+            context: Rc::new(Context::new(limits)),
+            call_frame: false,
+        }
+    }
+
     pub(crate) fn create_child(&'parent self) -> Self {
         Self {
-            named: HashMap::default(),
+            id: self.context.new_env_id(),
+            named: RefCell::default(),
+            parent: Some(self),
+            caller: None,
+            context: Rc::clone(&self.context),
+            call_frame: false,
+        }
+    }
+
+    pub(crate) fn create_call_frame(&'parent self, caller: &'parent Self) -> Self {
+        Self {
+            id: self.context.new_env_id(),
+            named: RefCell::default(),
             parent: Some(self),
+            caller: Some(caller),
+            context: Rc::clone(&self.context),
+            call_frame: true,
+        }
+    }
+
+    pub fn fuel_consumed(&self) -> u64 {
+        self.context.fuel_consumed()
+    }
+
+    pub(crate) fn enter_call(&self) -> Result<DepthGuard<'_>, EvalError> {
+        self.context.enter_call()
+    }
+
+    pub(crate) fn enter_eval(&self) -> Result<DepthGuard<'_>, EvalError> {
+        self.context.enter_eval()
+    }
+
+    pub(crate) fn charge_heap(&self, bytes: usize) -> Result<HeapCharge, EvalError> {
+        HeapCharge::new(&self.context, bytes)
+    }
+
+    pub(crate) fn check_list_len(&self, len: usize) -> Result<(), EvalError> {
+        self.context.check_list_len(len)
+    }
+
+    pub(crate) fn consume_fuel(&self) -> Result<(), EvalError> {
+        self.context.consume_fuel()
+    }
+
+    pub(crate) fn store_binding(&mut self, name: String, val: Val) -> Result<(), EvalError> {
+        self.store(
+            name,
+            NamedInfo::Binding {
+                val,
+                mutable: false,
+            },
+        )
+    }
+
+    pub(crate) fn store_mutable_binding(
+        &mut self,
+        name: String,
+        val: Val,
+    ) -> Result<(), EvalError> {
+        self.store(name, NamedInfo::Binding { val, mutable: true })
+    }
+
+    pub(crate) fn store_func(
+        &mut self,
+        name: String,
+        params: Vec<String>,
+        body: Stmt,
+    ) -> Result<(), EvalError> {
+        self.store(
+            name,
+            NamedInfo::Func {
+                params: params.into(),
+                body: Rc::new(body),
+            },
+        )
+    }
+
+    fn store(&mut self, name: String, info: NamedInfo) -> Result<(), EvalError> {
+        let new_size = info.heap_size(&name);
+
+        match self.named.get_mut().entry(name) {
+            Entry::Occupied(mut entry) => {
+                let old_size = entry.get().heap_size(entry.key());
+                self.context.reallocate(old_size, new_size)?;
+                entry.insert(info);
+            }
+            Entry::Vacant(entry) => {
+                self.context.allocate_binding(new_size)?;
+                entry.insert(info);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn assign_binding(&self, name: &str, new_val: Val) -> Result<(), EvalError> {
+        match self.named.borrow_mut().get_mut(name) {
+            Some(NamedInfo::Binding { val, mutable: true }) => {
+                self.context
+                    .reallocate(val.unshared_heap_size(), new_val.unshared_heap_size())?;
+                *val = new_val;
+                return Ok(());
+            }
+            Some(NamedInfo::Binding { mutable: false, .. }) => {
+                return Err(EvalError::AssignToImmutableBinding(name.to_owned()))
+            }
+            Some(NamedInfo::Func { .. }) => return Err(EvalError::AssignToFunc(name.to_owned())),
+            None => {}
+        }
+
+        match self.parent {
+            Some(parent) => parent.assign_binding(name, new_val),
+            None => Err(EvalError::BindingDoesNotExist(name.to_owned())),
+        }
+    }
+
+    pub(crate) fn get_binding(&self, name: &str) -> Result<Val, EvalError> {
+        match self.named.borrow().get(name) {
+            Some(NamedInfo::Binding { val, .. }) => return Ok(val.clone()),
+            Some(NamedInfo::Func { .. }) => {
+                return Err(EvalError::BindingDoesNotExist(name.to_owned()))
+            }
+            None => {}
         }
+
+        match self.parent {
+            Some(parent) => parent.get_binding(name),
+            None => Err(EvalError::BindingDoesNotExist(name.to_owned())),
+        }
+    }
+
+    pub(crate) fn get_func(&self, name: &str) -> Result<Func, EvalError> {
+        match self.named.borrow().get(name) {
+            Some(NamedInfo::Func { params, body }) => {
+                return Ok(Func {
+                    name: name.to_owned(),
+                    params: Rc::clone(params),
+                    body: Rc::clone(body),
+                    env_id: self.id,
+                    args: Vec::new().into(),
+                })
+            }
+            Some(NamedInfo::Binding {
+                val: Val::Func(func),
+                ..
+            }) => return Ok(*func.clone()),
+            Some(NamedInfo::Binding { .. }) => {
+                return Err(EvalError::FuncDoesNotExist(name.to_owned()))
+            }
+            None => {}
+        }
+
+        match self.parent {
+            Some(parent) => parent.get_func(name),
+            None => Err(EvalError::FuncDoesNotExist(name.to_owned())),
+        }
+    }
+
+    // Finds the environment `func` was defined in, which is what calls to it
+    // are evaluated in. Functions can be passed down to other calls, so the
+    // environments of the calls in progress are searched as well. This fails
+    // once the environment has gone away.
+    pub(crate) fn find_func_env(&self, func: &Func) -> Result<&Self, EvalError> {
+        let mut env = Some(self);
+        while let Some(current) = env {
+            if current.id == func.env_id {
+                return Ok(current);
+            }
+            env = current.parent;
+        }
+
+        self.find_env(func.env_id, &mut HashSet::new())
+            .ok_or_else(|| EvalError::FuncOutOfScope(func.name.clone()))
     }
-    pub(crate) fn store_binding(&mut self, name: String, val: Val) {
-        self.named.insert(name, NamedInfo::Binding(val));
+
+    fn find_env(&self, id: usize, visited: &mut HashSet<usize>) -> Option<&Self> {
+        if self.id == id {
+            return Some(self);
+        }
+        if !visited.insert(self.id) {
+            return None;
+        }
+
+        let parent = self.parent.and_then(|parent| parent.find_env(id, visited));
+        parent.or_else(|| self.caller.and_then(|caller| caller.find_env(id, visited)))
     }
 
-    pub(crate) fn store_func(&mut self, name: String, params: Vec<String>, body: Stmt) {
-        self.named.insert(name, NamedInfo::Func { params, body });
+    // Whether `func` was defined somewhere that goes away once the innermost
+    // function call returns.
+    pub(crate) fn is_call_local_func(&self, func: &Func) -> bool {
+        if self.id == func.env_id {
+            return true;
+        }
+
+        match self.parent {
+            Some(parent) if !self.call_frame => parent.is_call_local_func(func),
+            _ => false,
+        }
     }
 
-    pub(crate) fn get_binding(&self, name: &str) -> Result<Val, String> {
-        self.get_named_info(name)
-            .and_then(NamedInfo::into_binding)
-            .ok_or_else(|| format!("binding with name '{}' does not exist", name))
+    // Whether `name` is defined somewhere that goes away once the innermost
+    // function call returns.
+    pub(crate) fn is_call_local(&self, name: &str) -> bool {
+        if self.named.borrow().contains_key(name) {
+            return true;
+        }
+
+        match self.parent {
+            Some(parent) if !self.call_frame => parent.is_call_local(name),
+            _ => false,
+        }
     }
+}
+
+impl Drop for Env<'_> {
+    fn drop(&mut self) {
+        for (name, info) in self.named.get_mut().iter() {
+            self.context.release_binding(info.heap_size(name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assign_mutable_binding_in_parent() {
+        let mut parent = Env::default();
+        parent
+            .store_mutable_binding("x".to_owned(), Val::Number(1))
+            .unwrap();
+
+        let child = parent.create_child();
+        assert_eq!(child.assign_binding("x", Val::Number(2)), Ok(()));
+        assert_eq!(parent.get_binding("x"), Ok(Val::Number(2)));
+    }
+
+    #[test]
+    fn assign_nearest_binding() {
+        let mut parent = Env::default();
+        parent
+            .store_mutable_binding("x".to_owned(), Val::Number(1))
+            .unwrap();
+
+        let mut child = parent.create_child();
+        child
+            .store_mutable_binding("x".to_owned(), Val::Number(10))
+            .unwrap();
+
+        assert_eq!(child.assign_binding("x", Val::Number(20)), Ok(()));
+        assert_eq!(child.get_binding("x"), Ok(Val::Number(20)));
+        assert_eq!(parent.get_binding("x"), Ok(Val::Number(1)));
+    }
+
+    #[test]
+    fn cannot_assign_immutable_binding() {
+        let mut env = Env::default();
+        env.store_binding("x".to_owned(), Val::Number(1)).unwrap();
+
+        assert_eq!(
+            env.assign_binding("x", Val::Number(2)),
+            Err(EvalError::AssignToImmutableBinding("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn leaving_scope_frees_up_bindings() {
+        let mut parent = Env::with_limits(Limits {
+            max_bindings: Some(2),
+            ..Limits::default()
+        });
+        parent
+            .store_binding("x".to_owned(), Val::Number(1))
+            .unwrap();
+
+        {
+            let mut child = parent.create_child();
+            assert_eq!(child.store_binding("y".to_owned(), Val::Number(2)), Ok(()));
+            assert_eq!(
+                child.store_binding("z".to_owned(), Val::Number(3)),
+                Err(EvalError::TooManyBindings { max_bindings: 2 })
+            );
+        }
+
+        assert_eq!(parent.store_binding("x".to_owned(), Val::Unit), Ok(()));
+        assert_eq!(parent.store_binding("y".to_owned(), Val::Unit), Ok(()));
+    }
+
+    #[test]
+    fn cannot_assign_value_larger_than_heap_limit() {
+        let mut env = Env::with_limits(Limits {
+            max_heap_bytes: Some(200),
+            ..Limits::default()
+        });
+        env.store_mutable_binding("x".to_owned(), Val::Unit)
+            .unwrap();
 
-    pub(crate) fn get_func(&self, name: &str) -> Result<(Vec<String>, Stmt), String> {
-        self.get_named_info(name)
-            .and_then(NamedInfo::into_func)
-            .ok_or_else(|| format!("function with name '{}' does not exist.", name))
+        let big_val = (0..20).fold(Val::Unit, |val, _| Val::Ok(Box::new(val)));
+        assert_eq!(
+            env.assign_binding("x", big_val),
+            Err(EvalError::HeapLimitExceeded {
+                max_heap_bytes: 200
+            })
+        );
+        assert_eq!(env.get_binding("x"), Ok(Val::Unit));
     }
 
-    fn get_named_info(&self, name: &str) -> Option<NamedInfo> {
-        self.named
-            .get(name)
-            .cloned()
-            .or_else(|| self.parent.and_then(|parent| parent.get_named_info(name)))
+    #[test]
+    fn cannot_assign_non_existent_binding() {
+        assert_eq!(
+            Env::default().assign_binding("x", Val::Number(2)),
+            Err(EvalError::BindingDoesNotExist("x".to_owned()))
+        );
     }
 }
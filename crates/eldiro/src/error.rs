@@ -0,0 +1,253 @@
+use crate::expr::Op;
+use crate::syntax::SyntaxError;
+use crate::val::Val;
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalError {
+    BindingDoesNotExist(String),
+    FuncDoesNotExist(String),
+    FuncOutOfScope(String),
+    AssignToImmutableBinding(String),
+    AssignToFunc(String),
+    WrongNumberOfParams { expected: usize, got: usize },
+    NonNumberOperands,
+    DivisionByZero { op: Op, lhs: i32 },
+    Overflow { op: Op, lhs: i32, rhs: i32 },
+    NegationOverflow(i32),
+    NegativeExponent { lhs: i32, rhs: i32 },
+    NonNumberRangeBounds,
+    NonNumberCondition(Val),
+    NotIterable(Val),
+    NotResult(Val),
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    ReturnOutsideFunc,
+    PropagateOutsideFunc,
+    StackOverflow { max_call_depth: usize },
+    StackExhausted { max_stack_bytes: usize },
+    FuelExhausted { fuel: u64 },
+    ListTooLong { len: usize, max_list_len: usize },
+    ValTooDeep { max_depth: usize },
+    TooManyBindings { max_bindings: usize },
+    HeapLimitExceeded { max_heap_bytes: usize },
+    SyntaxErrors(Vec<SyntaxError>),
+}
+
+// The kinds of error a script can catch by name, and the names of the fields
+// a catch clause can bind from them. Fields that aren't numbers or values,
+// such as names and operators, are left out since scripts couldn't use them.
+const CATCHABLE_KINDS: &[(&str, &[&str])] = &[
+    ("BindingDoesNotExist", &[]),
+    ("FuncDoesNotExist", &[]),
+    ("FuncOutOfScope", &[]),
+    ("AssignToImmutableBinding", &[]),
+    ("AssignToFunc", &[]),
+    ("WrongNumberOfParams", &["expected", "got"]),
+    ("NonNumberOperands", &[]),
+    ("DivisionByZero", &["lhs"]),
+    ("Overflow", &["lhs", "rhs"]),
+    ("NegationOverflow", &["operand"]),
+    ("NegativeExponent", &["lhs", "rhs"]),
+    ("NonNumberRangeBounds", &[]),
+    ("NonNumberCondition", &["val"]),
+    ("NotIterable", &["val"]),
+    ("NotResult", &["val"]),
+    ("BreakOutsideLoop", &[]),
+    ("ContinueOutsideLoop", &[]),
+    ("ReturnOutsideFunc", &[]),
+    ("PropagateOutsideFunc", &[]),
+    ("StackOverflow", &["max_call_depth"]),
+    ("StackExhausted", &["max_stack_bytes"]),
+    ("SyntaxErrors", &[]),
+];
+
+impl EvalError {
+    // The names of the fields of the kind of error called `kind`, if it's one
+    // that can be caught.
+    pub(crate) fn catchable_fields(kind: &str) -> Option<&'static [&'static str]> {
+        CATCHABLE_KINDS
+            .iter()
+            .find(|(name, _)| *name == kind)
+            .map(|(_, fields)| *fields)
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::BindingDoesNotExist(_) => "BindingDoesNotExist",
+            Self::FuncDoesNotExist(_) => "FuncDoesNotExist",
+            Self::FuncOutOfScope(_) => "FuncOutOfScope",
+            Self::AssignToImmutableBinding(_) => "AssignToImmutableBinding",
+            Self::AssignToFunc(_) => "AssignToFunc",
+            Self::WrongNumberOfParams { .. } => "WrongNumberOfParams",
+            Self::NonNumberOperands => "NonNumberOperands",
+            Self::DivisionByZero { .. } => "DivisionByZero",
+            Self::Overflow { .. } => "Overflow",
+            Self::NegationOverflow(_) => "NegationOverflow",
+            Self::NegativeExponent { .. } => "NegativeExponent",
+            Self::NonNumberRangeBounds => "NonNumberRangeBounds",
+            Self::NonNumberCondition(_) => "NonNumberCondition",
+            Self::NotIterable(_) => "NotIterable",
+            Self::NotResult(_) => "NotResult",
+            Self::BreakOutsideLoop => "BreakOutsideLoop",
+            Self::ContinueOutsideLoop => "ContinueOutsideLoop",
+            Self::ReturnOutsideFunc => "ReturnOutsideFunc",
+            Self::PropagateOutsideFunc => "PropagateOutsideFunc",
+            Self::StackOverflow { .. } => "StackOverflow",
+            Self::StackExhausted { .. } => "StackExhausted",
+            Self::FuelExhausted { .. } => "FuelExhausted",
+            Self::ListTooLong { .. } => "ListTooLong",
+            Self::ValTooDeep { .. } => "ValTooDeep",
+            Self::TooManyBindings { .. } => "TooManyBindings",
+            Self::HeapLimitExceeded { .. } => "HeapLimitExceeded",
+            Self::SyntaxErrors(_) => "SyntaxErrors",
+        }
+    }
+
+    // The values of the fields `catchable_fields` names, in the same order.
+    pub(crate) fn fields(&self) -> Vec<Val> {
+        match self {
+            Self::WrongNumberOfParams { expected, got } => {
+                vec![count_val(*expected), count_val(*got)]
+            }
+            Self::DivisionByZero { lhs, .. } => vec![Val::Number(*lhs)],
+            Self::Overflow { lhs, rhs, .. } | Self::NegativeExponent { lhs, rhs } => {
+                vec![Val::Number(*lhs), Val::Number(*rhs)]
+            }
+            Self::NegationOverflow(operand) => vec![Val::Number(*operand)],
+            Self::NonNumberCondition(val) | Self::NotIterable(val) | Self::NotResult(val) => {
+                vec![val.clone()]
+            }
+            Self::StackOverflow { max_call_depth } => vec![count_val(*max_call_depth)],
+            Self::StackExhausted { max_stack_bytes } => vec![count_val(*max_stack_bytes)],
+            _ => Vec::new(),
+        }
+    }
+
+    // The value this error is about, if there is one.
+    pub(crate) fn val(&self) -> Option<&Val> {
+        match self {
+            Self::NonNumberCondition(val) | Self::NotIterable(val) | Self::NotResult(val) => {
+                Some(val)
+            }
+            _ => None,
+        }
+    }
+
+    // Running out of fuel or memory can't be caught by the script, since
+    // otherwise it could keep on running by catching the error in a loop.
+    pub(crate) fn is_catchable(&self) -> bool {
+        !matches!(
+            self,
+            Self::FuelExhausted { .. }
+                | Self::ListTooLong { .. }
+                | Self::ValTooDeep { .. }
+                | Self::TooManyBindings { .. }
+                | Self::HeapLimitExceeded { .. }
+        )
+    }
+}
+
+// Counts that don't fit in a number are capped, rather than wrapping around.
+fn count_val(count: usize) -> Val {
+    Val::Number(i32::try_from(count).unwrap_or(i32::MAX))
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BindingDoesNotExist(name) => {
+                write!(f, "binding with name '{}' does not exist", name)
+            }
+            Self::FuncDoesNotExist(name) => {
+                write!(f, "function with name '{}' does not exist.", name)
+            }
+            Self::FuncOutOfScope(name) => write!(
+                f,
+                "function with name '{}' cannot be called outside of the scope it was defined in",
+                name
+            ),
+            Self::AssignToImmutableBinding(name) => {
+                write!(f, "cannot assign to immutable binding with name '{}'", name)
+            }
+            Self::AssignToFunc(name) => {
+                write!(f, "cannot assign to function with name '{}'", name)
+            }
+            Self::WrongNumberOfParams { expected, got } => {
+                write!(f, "expected {} parameters, got {}", expected, got)
+            }
+            Self::NonNumberOperands => {
+                write!(
+                    f,
+                    "cannot evaluate operation whose operands are not numbers"
+                )
+            }
+            Self::DivisionByZero { op, lhs } => {
+                write!(f, "division by zero evaluating {} {} 0", lhs, op)
+            }
+            Self::Overflow { op, lhs, rhs } => {
+                write!(f, "arithmetic overflow evaluating {} {} {}", lhs, op, rhs)
+            }
+            Self::NegationOverflow(operand) => {
+                write!(f, "arithmetic overflow evaluating -({})", operand)
+            }
+            Self::NegativeExponent { lhs, rhs } => {
+                write!(f, "cannot raise {} to negative power {}", lhs, rhs)
+            }
+            Self::NonNumberRangeBounds => {
+                write!(f, "cannot create a range whose bounds are not numbers")
+            }
+            Self::NonNumberCondition(val) => {
+                write!(f, "expected a number as condition, got {}", val)
+            }
+            Self::NotIterable(val) => write!(f, "cannot iterate over {}", val),
+            Self::NotResult(val) => write!(f, "cannot use '?' on {}", val),
+            Self::BreakOutsideLoop => write!(f, "cannot use 'break' outside of a loop"),
+            Self::ContinueOutsideLoop => write!(f, "cannot use 'continue' outside of a loop"),
+            Self::ReturnOutsideFunc => write!(f, "cannot use 'return' outside of a function"),
+            Self::PropagateOutsideFunc => write!(f, "cannot use '?' outside of a function"),
+            Self::StackOverflow { max_call_depth } => write!(
+                f,
+                "stack overflow in Eldiro code: more than {} nested function calls",
+                max_call_depth
+            ),
+            Self::StackExhausted { max_stack_bytes } => write!(
+                f,
+                "stack overflow in Eldiro code: evaluation used more than {} bytes of native stack",
+                max_stack_bytes
+            ),
+            Self::FuelExhausted { fuel } => {
+                write!(f, "evaluation budget exhausted after {} steps", fuel)
+            }
+            Self::ListTooLong { len, max_list_len } => write!(
+                f,
+                "cannot create a list of {} items: the limit is {}",
+                len, max_list_len
+            ),
+            Self::TooManyBindings { max_bindings } => {
+                write!(f, "cannot have more than {} live bindings", max_bindings)
+            }
+            Self::ValTooDeep { max_depth } => {
+                write!(f, "value is nested more than {} levels deep", max_depth)
+            }
+            Self::HeapLimitExceeded { max_heap_bytes } => write!(
+                f,
+                "memory limit exceeded: values may take up at most {} bytes",
+                max_heap_bytes
+            ),
+            Self::SyntaxErrors(errors) => {
+                write!(f, "cannot evaluate a program with syntax errors: ")?;
+                for (idx, error) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
@@ -1,14 +1,33 @@
 use crate::env::Env;
+use crate::error::EvalError;
+use crate::lexer::TokenKind;
+use crate::parser::{Checkpoint, Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
 use crate::utils;
 use crate::val::Val;
+use std::convert::TryFrom;
+use std::fmt;
 
+pub(crate) use assignment::Assignment;
 pub(crate) use binding_usage::BindingUsage;
 pub(crate) use block::Block;
-pub(crate) use func_call::FuncCall;
+pub(crate) use for_loop::For;
+pub(crate) use func_call::{FuncCall, Tail};
+pub(crate) use if_else::If;
+pub(crate) use list::List;
+pub(crate) use try_catch::TryCatch;
+pub(crate) use while_loop::While;
 
+mod assignment;
 mod binding_usage;
 mod block;
+mod for_loop;
 mod func_call;
+mod if_else;
+mod list;
+mod try_catch;
+mod while_loop;
 
 #[cfg(test)]
 mod tests {
@@ -16,11 +35,95 @@ mod tests {
     use binding_usage::BindingUsage;
 
     use super::*;
+
+    // Shows an operation with every level parenthesized, to make precedence
+    // visible.
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Operation { lhs, rhs, op } => format!("({} {} {})", show(lhs), op, show(rhs)),
+            Expr::Number(Number(n)) => n.to_string(),
+            _ => panic!("cannot show {:?}", expr),
+        }
+    }
+
     #[test]
     fn parse_number() {
         assert_eq!(Number::new("123"), Ok(("", Number(123))));
     }
 
+    #[test]
+    fn parse_number_with_radix_prefix() {
+        assert_eq!(Number::new("0xff"), Ok(("", Number(255))));
+        assert_eq!(Number::new("0xFF"), Ok(("", Number(255))));
+        assert_eq!(Number::new("0o17"), Ok(("", Number(15))));
+        assert_eq!(Number::new("0b1010 "), Ok((" ", Number(10))));
+        assert_eq!(Number::new("0x7fff_ffff"), Ok(("", Number(i32::MAX))));
+    }
+
+    #[test]
+    fn parse_number_with_separators() {
+        assert_eq!(Number::new("1_000_000"), Ok(("", Number(1_000_000))));
+        assert_eq!(Number::new("1__0_"), Ok(("", Number(10))));
+        assert_eq!(Number::new("0b_1"), Ok(("", Number(1))));
+    }
+
+    #[test]
+    fn parse_malformed_numbers() {
+        assert_eq!(
+            Number::new("0x"),
+            Err("expected digits in hexadecimal literal".to_owned())
+        );
+        assert_eq!(
+            Number::new("0b_"),
+            Err("expected digits in binary literal".to_owned())
+        );
+        assert_eq!(
+            Number::new("0b102"),
+            Err("invalid digit '2' in binary literal".to_owned())
+        );
+        assert_eq!(
+            Number::new("0xfg"),
+            Err("invalid digit 'g' in hexadecimal literal".to_owned())
+        );
+        assert_eq!(
+            Number::new("12ab"),
+            Err("invalid digit 'a' in decimal literal".to_owned())
+        );
+        assert_eq!(Number::new("_1"), Err("expected number".to_owned()));
+    }
+
+    #[test]
+    fn parse_malformed_number_as_expr() {
+        assert_eq!(
+            Expr::new("0x"),
+            Err("expected digits in hexadecimal literal".to_owned())
+        );
+        assert_eq!(
+            Expr::new("f 0x"),
+            Err("expected digits in hexadecimal literal".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_negative_number_with_radix_prefix() {
+        assert_eq!(
+            Expr::new("-0x8000_0000"),
+            Ok(("", Expr::Number(Number(i32::MIN))))
+        );
+        assert_eq!(
+            Expr::new("-0x8000_0001"),
+            Err("number literal -0x8000_0001 is too small".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_too_large_number() {
+        assert_eq!(
+            Number::new("2147483648"),
+            Err("number literal 2147483648 is too large".to_owned())
+        );
+    }
+
     #[test]
     fn parse_add_op() {
         assert_eq!(Op::new("+"), Ok(("", Op::Add)));
@@ -40,9 +143,37 @@ mod tests {
     fn parse_div_op() {
         assert_eq!(Op::new("/"), Ok(("", Op::Div)));
     }
+    #[test]
+    fn parse_longest_op() {
+        assert_eq!(Op::new("**2"), Ok(("2", Op::Pow)));
+        assert_eq!(Op::new(">> 1"), Ok((" 1", Op::Shr)));
+        assert_eq!(Op::new("| x"), Ok((" x", Op::BitOr)));
+    }
+
+    #[test]
+    fn parse_pipe_is_not_op() {
+        assert!(Op::new("|> f").is_err());
+    }
+
+    #[test]
+    fn parse_expr_reports_lexer_error() {
+        assert_eq!(
+            Expr::new("1 + €"),
+            Err("unexpected character '€'".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_missing_expr() {
+        assert_eq!(
+            Expr::new("1 +"),
+            Err("expected expression after `+`".to_owned())
+        );
+    }
+
     #[test]
     fn parse_wrong_op() {
-        assert_eq!(Op::new("_"), Err("expected /".to_owned()));
+        assert_eq!(Op::new("_"), Err("expected operator".to_owned()));
     }
 
     #[test]
@@ -127,6 +258,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eval_div_by_zero() {
+        assert_eq!(
+            Expr::Operation {
+                lhs: Box::new(Expr::Number(Number(1))),
+                rhs: Box::new(Expr::Number(Number(0))),
+                op: Op::Div,
+            }
+            .eval(&Env::default()),
+            Err(Unwind::Error(EvalError::DivisionByZero {
+                op: Op::Div,
+                lhs: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn eval_add_with_overflow() {
+        assert_eq!(
+            Expr::Operation {
+                lhs: Box::new(Expr::Number(Number(i32::MAX))),
+                rhs: Box::new(Expr::Number(Number(1))),
+                op: Op::Add,
+            }
+            .eval(&Env::default()),
+            Err(Unwind::Error(EvalError::Overflow {
+                op: Op::Add,
+                lhs: i32::MAX,
+                rhs: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn eval_div_with_overflow() {
+        assert_eq!(
+            Op::Div.apply(i32::MIN, -1),
+            Err(EvalError::Overflow {
+                op: Op::Div,
+                lhs: i32::MIN,
+                rhs: -1
+            })
+        );
+    }
+
+    #[test]
+    fn catch_div_by_zero() {
+        let (_, expr) = Expr::new("try { 10 / 0 } catch e { e }").unwrap();
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Ok(Val::Error(Box::new(EvalError::DivisionByZero {
+                op: Op::Div,
+                lhs: 10
+            })))
+        );
+    }
+
+    #[test]
+    fn eval_random_operations_without_panicking() {
+        const NUMBERS: &[i32] = &[
+            0,
+            1,
+            -1,
+            2,
+            7,
+            i32::MAX,
+            i32::MIN,
+            i32::MAX - 1,
+            i32::MIN + 1,
+        ];
+        const OPS: &[Op] = &[
+            Op::Add,
+            Op::Sub,
+            Op::Mul,
+            Op::Div,
+            Op::Rem,
+            Op::Pow,
+            Op::BitAnd,
+            Op::BitOr,
+            Op::BitXor,
+            Op::Shl,
+            Op::Shr,
+        ];
+
+        let mut next = crate::utils::xorshift(0x2545_f491_4f6c_dd1d);
+
+        fn gen(depth: usize, next: &mut impl FnMut(usize) -> usize) -> Expr {
+            if depth == 0 || next(3) == 0 {
+                return Expr::Number(Number(NUMBERS[next(NUMBERS.len())]));
+            }
+
+            Expr::Operation {
+                lhs: Box::new(gen(depth - 1, next)),
+                rhs: Box::new(gen(depth - 1, next)),
+                op: OPS[next(OPS.len())],
+            }
+        }
+
+        for _ in 0..10_000 {
+            let _ = gen(6, &mut next).eval(&Env::default());
+        }
+    }
+
+    #[test]
+    fn eval_consumes_fuel_per_expr() {
+        let env = Env::default();
+
+        let (_, expr) = Expr::new("1 + 2").unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::Number(3)));
+        assert_eq!(env.fuel_consumed(), 3);
+    }
+
+    #[test]
+    fn eval_tail_consumes_as_much_fuel_as_eval() {
+        let (_, expr) = Expr::new("{ { if 1 { { 2 } } else { 3 } } }").unwrap();
+
+        let env = Env::default();
+        expr.eval(&env).unwrap();
+        let tail_env = Env::default();
+        expr.eval_tail(&tail_env).unwrap();
+
+        assert_eq!(tail_env.fuel_consumed(), env.fuel_consumed());
+    }
+
     #[test]
     fn parse_number_as_expr() {
         assert_eq!(Expr::new("3"), Ok(("", Expr::Number(Number(3)))));
@@ -167,7 +422,7 @@ mod tests {
                 op: Op::Add,
             }
             .eval(&Env::default()),
-            Err("cannot evaluate operation whose operands are not numbers".to_owned())
+            Err(Unwind::Error(EvalError::NonNumberOperands))
         )
     }
 
@@ -186,142 +441,1192 @@ mod tests {
     }
 
     #[test]
-    fn eval_func_call() {
-        let mut env = Env::default();
-
-        env.store_func(
-            "add".to_owned(),
-            vec!["x".to_owned(), "y".to_owned()],
-            Stmt::Expr(Expr::Operation {
-                lhs: Box::new(Expr::BindingUsage(BindingUsage {
-                    name: "x".to_owned(),
-                })),
-                rhs: Box::new(Expr::BindingUsage(BindingUsage {
-                    name: "y".to_owned(),
-                })),
-                op: Op::Add,
-            }),
+    fn parse_func_call_binds_tighter_than_operation() {
+        assert_eq!(
+            Expr::new("add 1 2 + 3"),
+            Ok((
+                "",
+                Expr::Operation {
+                    lhs: Box::new(Expr::FuncCall(FuncCall {
+                        callee: "add".to_owned(),
+                        params: vec![Expr::Number(Number(1)), Expr::Number(Number(2))]
+                    })),
+                    rhs: Box::new(Expr::Number(Number(3))),
+                    op: Op::Add,
+                }
+            ))
         );
+    }
 
+    #[test]
+    fn parse_func_call_on_rhs_of_operation() {
         assert_eq!(
-            Expr::FuncCall(FuncCall {
-                callee: "add".to_owned(),
-                params: vec![Expr::Number(Number(2)), Expr::Number(Number(2))]
-            })
-            .eval(&mut env),
-            Ok(Val::Number(4))
+            Expr::new("1 * f x"),
+            Ok((
+                "",
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number(1))),
+                    rhs: Box::new(Expr::FuncCall(FuncCall {
+                        callee: "f".to_owned(),
+                        params: vec![Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })]
+                    })),
+                    op: Op::Mul,
+                }
+            ))
         );
     }
-}
-
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub struct Number(pub i32);
 
-impl Number {
-    pub fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, digits) = utils::extract_digits(s)?;
-        let digits = digits.parse().unwrap();
-        Ok((s, Self(digits)))
+    #[test]
+    fn parse_func_call_with_func_as_arg() {
+        assert_eq!(
+            Expr::new("f g x"),
+            Ok((
+                "",
+                Expr::FuncCall(FuncCall {
+                    callee: "f".to_owned(),
+                    params: vec![
+                        Expr::BindingUsage(BindingUsage {
+                            name: "g".to_owned()
+                        }),
+                        Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })
+                    ]
+                })
+            ))
+        );
     }
-}
-
-#[derive(PartialEq, Debug, Clone, Copy)]
-pub enum Op {
-    Add,
-    Sub,
-    Mul,
-    Div,
-}
 
-impl Op {
-    pub fn new(s: &str) -> Result<(&str, Self), String> {
-        utils::tag("+", s)
-            .map(|s| (s, Self::Add))
-            .or_else(|_| utils::tag("-", s).map(|s| (s, Self::Sub)))
-            .or_else(|_| utils::tag("*", s).map(|s| (s, Self::Mul)))
-            .or_else(|_| utils::tag("/", s).map(|s| (s, Self::Div)))
+    #[test]
+    fn parse_func_call_with_parenthesized_args() {
+        assert_eq!(
+            Expr::new("f (g x) ( 1 + 2 ) [3]"),
+            Ok((
+                "",
+                Expr::FuncCall(FuncCall {
+                    callee: "f".to_owned(),
+                    params: vec![
+                        Expr::FuncCall(FuncCall {
+                            callee: "g".to_owned(),
+                            params: vec![Expr::BindingUsage(BindingUsage {
+                                name: "x".to_owned()
+                            })]
+                        }),
+                        Expr::Operation {
+                            lhs: Box::new(Expr::Number(Number(1))),
+                            rhs: Box::new(Expr::Number(Number(2))),
+                            op: Op::Add,
+                        },
+                        Expr::List(List {
+                            items: vec![Expr::Number(Number(3))]
+                        })
+                    ]
+                })
+            ))
+        );
     }
-}
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) enum Expr {
-    Number(Number),
-    Operation {
-        lhs: Box<Self>,
-        rhs: Box<Self>,
-        op: Op,
-    },
-    FuncCall(FuncCall),
-    BindingUsage(BindingUsage),
-    Block(Block),
-}
 
-impl Expr {
-    pub fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_operation(s).or_else(|_| Self::new_non_operation(s))
+    #[test]
+    fn parse_propagate_on_func_call() {
+        assert_eq!(
+            Expr::new("f x?"),
+            Ok((
+                "",
+                Expr::Propagate(Box::new(Expr::FuncCall(FuncCall {
+                    callee: "f".to_owned(),
+                    params: vec![Expr::BindingUsage(BindingUsage {
+                        name: "x".to_owned()
+                    })]
+                })))
+            ))
+        );
     }
 
-    fn new_non_operation(s: &str) -> Result<(&str, Self), String> {
-        Self::new_number(s)
-            .or_else(|_| FuncCall::new(s).map(|(s, func_call)| (s, Self::FuncCall(func_call))))
-            .or_else(|_| {
-                BindingUsage::new(s)
-                    .map(|(s, binding_usage)| (s, Self::BindingUsage(binding_usage)))
-            })
-            .or_else(|_| Block::new(s).map(|(s, block)| (s, Self::Block(block))))
+    #[test]
+    fn parse_unclosed_paren() {
+        assert_eq!(
+            Expr::new("(1 + 2"),
+            Err("expected `)` after `2`".to_owned())
+        );
     }
 
-    pub fn new_operation(s: &str) -> Result<(&str, Self), String> {
-        let (s, lhs) = Expr::new_non_operation(s)?;
-        let (s, _) = utils::extract_whitespace(s);
-
-        let (s, op) = Op::new(s)?;
-        let (s, _) = utils::extract_whitespace(s);
+    #[test]
+    fn eval_parenthesized_operation() {
+        let (_, expr) = Expr::new("(1 + 2) * (10 - 3)").unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Number(21)));
+    }
 
-        let (s, rhs) = Expr::new_non_operation(s)?;
+    #[test]
+    fn eval_nested_func_calls() {
+        let mut env = Env::default();
+        let (_, add) = Stmt::new("x + y").unwrap();
+        env.store_func("add".to_owned(), vec!["x".to_owned(), "y".to_owned()], add)
+            .unwrap();
 
-        let expr = Self::Operation {
-            lhs: Box::new(lhs),
-            rhs: Box::new(rhs),
-            op,
-        };
-        Ok((s, expr))
+        let (_, expr) = Expr::new("add (add 1 2) (4 - 1) + add 1 1").unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::Number(8)));
     }
 
-    pub fn new_number(s: &str) -> Result<(&str, Self), String> {
-        Number::new(s).map(|(s, number)| (s, Self::Number(number)))
+    #[test]
+    fn parse_negative_number() {
+        assert_eq!(Expr::new("-5"), Ok(("", Expr::Number(Number(-5)))));
+        assert_eq!(
+            Expr::new("-2147483648"),
+            Ok(("", Expr::Number(Number(i32::MIN))))
+        );
     }
 
-    pub(crate) fn eval(&self, env: &Env) -> Result<Val, String> {
-        match self {
-            Self::Number(Number(number)) => Ok(Val::Number(*number)),
-            Self::Operation { lhs, rhs, op } => {
-                let lhs = lhs.eval(env)?;
-                let rhs = rhs.eval(env)?;
-
-                let (lhs, rhs) = match (lhs, rhs) {
-                    (Val::Number(lhs), Val::Number(rhs)) => (lhs, rhs),
-                    _ => {
-                        return Err(
-                            "cannot evaluate operation whose operands are not numbers".to_owned()
-                        )
-                    }
-                };
-
-                let res = match op {
-                    Op::Add => lhs + rhs,
-                    Op::Sub => lhs - rhs,
-                    Op::Mul => lhs * rhs,
-                    Op::Div => lhs / rhs,
-                };
-
-                Ok(Val::Number(res))
-            }
+    #[test]
+    fn parse_negation_binds_tighter_than_operation() {
+        assert_eq!(
+            Expr::new("-x * 2"),
+            Ok((
+                "",
+                Expr::Operation {
+                    lhs: Box::new(Expr::Unary {
+                        op: UnaryOp::Neg,
+                        expr: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })),
+                    }),
+                    rhs: Box::new(Expr::Number(Number(2))),
+                    op: Op::Mul,
+                }
+            ))
+        );
+    }
 
-            Self::FuncCall(func_call) => func_call.eval(env),
+    #[test]
+    fn parse_negation_binds_looser_than_pow() {
+        assert_eq!(
+            Expr::new("-x ** 2"),
+            Ok((
+                "",
+                Expr::Unary {
+                    op: UnaryOp::Neg,
+                    expr: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })),
+                        rhs: Box::new(Expr::Number(Number(2))),
+                        op: Op::Pow,
+                    }),
+                }
+            ))
+        );
+    }
 
-            Self::BindingUsage(binding_usage) => binding_usage.eval(env),
+    #[test]
+    fn parse_negation_binds_looser_than_func_call() {
+        assert_eq!(
+            Expr::new("!f x"),
+            Ok((
+                "",
+                Expr::Unary {
+                    op: UnaryOp::Not,
+                    expr: Box::new(Expr::FuncCall(FuncCall {
+                        callee: "f".to_owned(),
+                        params: vec![Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })]
+                    })),
+                }
+            ))
+        );
+    }
 
-            Self::Block(block) => block.eval(env),
+    #[test]
+    fn parse_binary_minus_before_negative_number() {
+        assert_eq!(
+            Expr::new("x -1"),
+            Ok((
+                "",
+                Expr::Operation {
+                    lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "x".to_owned()
+                    })),
+                    rhs: Box::new(Expr::Number(Number(1))),
+                    op: Op::Sub,
+                }
+            ))
+        );
+        assert_eq!(
+            Expr::new("1 - -1"),
+            Ok((
+                "",
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number(1))),
+                    rhs: Box::new(Expr::Number(Number(-1))),
+                    op: Op::Sub,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_unary_ops() {
+        let env = Env::default();
+        let eval = |s| Expr::new(s).unwrap().1.eval(&env);
+
+        assert_eq!(eval("-(3 - 5)"), Ok(Val::Number(2)));
+        assert_eq!(eval("--3"), Ok(Val::Number(3)));
+        assert_eq!(eval("!0"), Ok(Val::Number(1)));
+        assert_eq!(eval("!7"), Ok(Val::Number(0)));
+        assert_eq!(eval("!!7"), Ok(Val::Number(1)));
+    }
+
+    #[test]
+    fn eval_negation_overflow() {
+        let (_, expr) = Expr::new("-(-2147483648)").unwrap();
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(Unwind::Error(EvalError::NegationOverflow(i32::MIN)))
+        );
+    }
+
+    #[test]
+    fn parse_operations_by_precedence() {
+        let (_, expr) = Expr::new("1 | 2 ^ 3 & 4 << 5 + 6 * 7 ** 8").unwrap();
+        let expected = "(1 | (2 ^ (3 & (4 << (5 + (6 * (7 ** 8)))))))";
+
+        assert_eq!(show(&expr), expected);
+
+        let (_, expr) = Expr::new("1 ** 2 * 3 + 4 << 5 & 6 ^ 7 | 8").unwrap();
+        let expected = "(((((((1 ** 2) * 3) + 4) << 5) & 6) ^ 7) | 8)";
+
+        assert_eq!(show(&expr), expected);
+    }
+
+    #[test]
+    fn parse_operations_by_associativity() {
+        let (_, expr) = Expr::new("1 - 2 - 3 % 4 / 5").unwrap();
+        assert_eq!(show(&expr), "((1 - 2) - ((3 % 4) / 5))");
+
+        let (_, expr) = Expr::new("2 ** 3 ** 2").unwrap();
+        assert_eq!(show(&expr), "(2 ** (3 ** 2))");
+    }
+
+    #[test]
+    fn parse_long_chain_of_operations() {
+        let chain = vec!["1"; 1000].join(" + ");
+        let (_, expr) = Expr::new(&chain).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Number(1000)));
+
+        let chain = vec!["1"; 2000].join(" + ");
+        assert_eq!(
+            Expr::new(&chain),
+            Err("expression has too many operators".to_owned())
+        );
+    }
+
+    #[test]
+    fn eval_extended_ops() {
+        let env = Env::default();
+        let eval = |s| Expr::new(s).unwrap().1.eval(&env);
+
+        assert_eq!(eval("1 + 2 * 3"), Ok(Val::Number(7)));
+        assert_eq!(eval("7 % 3"), Ok(Val::Number(1)));
+        assert_eq!(eval("-7 % 3"), Ok(Val::Number(2)));
+        assert_eq!(eval("7 % -3"), Ok(Val::Number(1)));
+        assert_eq!(eval("2 ** 3 ** 2"), Ok(Val::Number(512)));
+        assert_eq!(eval("-2 ** 2"), Ok(Val::Number(-4)));
+        assert_eq!(eval("~1 ** 2 * 3"), Ok(Val::Number(-6)));
+        assert_eq!(eval("12 & 10"), Ok(Val::Number(8)));
+        assert_eq!(eval("12 | 10"), Ok(Val::Number(14)));
+        assert_eq!(eval("12 ^ 10"), Ok(Val::Number(6)));
+        assert_eq!(eval("~5"), Ok(Val::Number(-6)));
+        assert_eq!(eval("1 << 4 + 1"), Ok(Val::Number(32)));
+        assert_eq!(eval("-16 >> 2"), Ok(Val::Number(-4)));
+        assert_eq!(eval("1 << 31"), Ok(Val::Number(i32::MIN)));
+    }
+
+    #[test]
+    fn eval_extended_ops_with_errors() {
+        let env = Env::default();
+        let eval = |s| Expr::new(s).unwrap().1.eval(&env);
+
+        assert_eq!(
+            eval("1 % 0"),
+            Err(Unwind::Error(EvalError::DivisionByZero {
+                op: Op::Rem,
+                lhs: 1
+            }))
+        );
+        assert_eq!(
+            eval("2 ** 31"),
+            Err(Unwind::Error(EvalError::Overflow {
+                op: Op::Pow,
+                lhs: 2,
+                rhs: 31
+            }))
+        );
+        assert_eq!(
+            eval("2 ** -1"),
+            Err(Unwind::Error(EvalError::NegativeExponent {
+                lhs: 2,
+                rhs: -1
+            }))
+        );
+        assert_eq!(
+            eval("1 << 32"),
+            Err(Unwind::Error(EvalError::Overflow {
+                op: Op::Shl,
+                lhs: 1,
+                rhs: 32
+            }))
+        );
+        assert_eq!(
+            eval("1 >> -1"),
+            Err(Unwind::Error(EvalError::Overflow {
+                op: Op::Shr,
+                lhs: 1,
+                rhs: -1
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_range() {
+        assert_eq!(
+            Expr::new("0..n + 1"),
+            Ok((
+                "",
+                Expr::Range {
+                    start: Box::new(Expr::Number(Number(0))),
+                    end: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "n".to_owned()
+                        })),
+                        rhs: Box::new(Expr::Number(Number(1))),
+                        op: Op::Add,
+                    }),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_pipes() {
+        assert_eq!(
+            Expr::new("x |> f |> g 2"),
+            Ok((
+                "",
+                Expr::Pipe {
+                    arg: Box::new(Expr::Pipe {
+                        arg: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })),
+                        call: FuncCall {
+                            callee: "f".to_owned(),
+                            params: vec![],
+                        },
+                    }),
+                    call: FuncCall {
+                        callee: "g".to_owned(),
+                        params: vec![Expr::Number(Number(2))],
+                    },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_pipe_from_call_and_operation() {
+        assert_eq!(
+            Expr::new("f 1 + 2 |> g"),
+            Ok((
+                "",
+                Expr::Pipe {
+                    arg: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::FuncCall(FuncCall {
+                            callee: "f".to_owned(),
+                            params: vec![Expr::Number(Number(1))],
+                        })),
+                        rhs: Box::new(Expr::Number(Number(2))),
+                        op: Op::Add,
+                    }),
+                    call: FuncCall {
+                        callee: "g".to_owned(),
+                        params: vec![],
+                    },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_pipe_without_call() {
+        assert!(Expr::new("1 |> 2").is_err());
+    }
+
+    #[test]
+    fn eval_pipes_pass_value_as_last_arg() {
+        let mut env = Env::default();
+        let (_, double) = Stmt::new("x * 2").unwrap();
+        env.store_func("double".to_owned(), vec!["x".to_owned()], double)
+            .unwrap();
+        let (_, sub) = Stmt::new("x - y").unwrap();
+        env.store_func("sub".to_owned(), vec!["x".to_owned(), "y".to_owned()], sub)
+            .unwrap();
+
+        let (_, expr) = Expr::new("5 |> double |> sub 100").unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::Number(90)));
+    }
+
+    #[test]
+    fn parse_break_with_value() {
+        assert_eq!(
+            Expr::new("break 1"),
+            Ok(("", Expr::Break(Some(Box::new(Expr::Number(Number(1)))))))
+        );
+    }
+
+    #[test]
+    fn parse_break_without_value_followed_by_newline() {
+        assert_eq!(Expr::new("break\n1"), Ok(("\n1", Expr::Break(None))));
+    }
+
+    #[test]
+    fn parse_break_without_value_at_end_of_block() {
+        assert_eq!(Expr::new("break }"), Ok((" }", Expr::Break(None))));
+    }
+
+    #[test]
+    fn cannot_parse_break_with_invalid_value() {
+        assert_eq!(
+            Expr::new("break {"),
+            Err("expected `}` after `{`".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_binding_usage_starting_with_continue() {
+        assert_eq!(
+            Expr::new("continued"),
+            Ok((
+                "",
+                Expr::BindingUsage(BindingUsage {
+                    name: "continued".to_owned()
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_for_with_continue_and_break() {
+        let (_, expr) = Expr::new("for x in [1, 2, 3] { if x - 2 { continue } break x }").unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Number(2)));
+    }
+
+    #[test]
+    fn eval_loop_updating_outer_binding() {
+        let (_, expr) = Expr::new(
+            "{
+    let mut i = 0
+    let mut sum = 0
+    while 4 - i {
+        i = i + 1
+        sum = sum + i
+    }
+    sum
+}",
+        )
+        .unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Number(10)));
+    }
+
+    #[test]
+    fn parse_return_without_value() {
+        assert_eq!(Expr::new("return"), Ok(("", Expr::Return(None))));
+    }
+
+    #[test]
+    fn eval_return_outside_function() {
+        assert_eq!(
+            Expr::Return(Some(Box::new(Expr::Number(Number(1))))).eval(&Env::default()),
+            Err(Unwind::Return(Val::Number(1)))
+        );
+    }
+
+    #[test]
+    fn parse_err_with_propagate() {
+        assert_eq!(
+            Expr::new("Err x?"),
+            Ok((
+                "",
+                Expr::Err(Box::new(Expr::Propagate(Box::new(Expr::BindingUsage(
+                    BindingUsage {
+                        name: "x".to_owned()
+                    }
+                )))))
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_propagate_ok() {
+        assert_eq!(
+            Expr::Propagate(Box::new(Expr::Ok(Box::new(Expr::Number(Number(1))))))
+                .eval(&Env::default()),
+            Ok(Val::Number(1))
+        );
+    }
+
+    #[test]
+    fn eval_propagate_err() {
+        assert_eq!(
+            Expr::Propagate(Box::new(Expr::Err(Box::new(Expr::Number(Number(1))))))
+                .eval(&Env::default()),
+            Err(Unwind::Propagate(Val::Err(Box::new(Val::Number(1)))))
+        );
+    }
+
+    #[test]
+    fn eval_propagate_non_result() {
+        assert_eq!(
+            Expr::Propagate(Box::new(Expr::Number(Number(1)))).eval(&Env::default()),
+            Err(Unwind::Error(EvalError::NotResult(Val::Number(1))))
+        );
+    }
+
+    #[test]
+    fn eval_break_outside_loop() {
+        assert_eq!(
+            Expr::Break(None).eval(&Env::default()),
+            Err(Unwind::Break(Val::Unit))
+        );
+    }
+
+    #[test]
+    fn eval_func_call() {
+        let mut env = Env::default();
+
+        env.store_func(
+            "add".to_owned(),
+            vec!["x".to_owned(), "y".to_owned()],
+            Stmt::Expr(Expr::Operation {
+                lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "x".to_owned(),
+                })),
+                rhs: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "y".to_owned(),
+                })),
+                op: Op::Add,
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Expr::FuncCall(FuncCall {
+                callee: "add".to_owned(),
+                params: vec![Expr::Number(Number(2)), Expr::Number(Number(2))]
+            })
+            .eval(&env),
+            Ok(Val::Number(4))
+        );
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Number(pub i32);
+
+impl Parsable for Number {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Number, |p| Self::parse_with_sign(p, ""))
+    }
+}
+
+impl Number {
+    fn parse_with_sign(p: &mut Parser, sign: &str) -> Result<Self, String> {
+        // The literal is only bumped once it's known to be valid, so that
+        // errors point at it.
+        let literal = match p.peek() {
+            Some(token) if token.kind == TokenKind::Number => token.text,
+            _ => return Err(p.expected("number")),
+        };
+
+        let (radix, kind, digits) = match literal.get(..2) {
+            Some("0x") => (16, "hexadecimal", &literal[2..]),
+            Some("0o") => (8, "octal", &literal[2..]),
+            Some("0b") => (2, "binary", &literal[2..]),
+            _ => (10, "decimal", literal),
+        };
+        let digits: String = digits.chars().filter(|&c| c != '_').collect();
+
+        if let Some(c) = digits.chars().find(|c| !c.is_digit(radix)) {
+            return Err(format!("invalid digit '{}' in {} literal", c, kind));
+        }
+        if digits.is_empty() {
+            return Err(format!("expected digits in {} literal", kind));
+        }
+
+        let number = i32::from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|_| {
+            let bound = if sign.is_empty() { "large" } else { "small" };
+            format!("number literal {}{} is too {}", sign, literal, bound)
+        })?;
+        p.bump();
+
+        Ok(Self(number))
+    }
+}
+
+/// A binary operator. From loosest to tightest binding, the precedence levels
+/// are `|`, `^`, `&`, `<<` and `>>`, `+` and `-`, `*`, `/` and `%`, and
+/// finally `**`. All operators are left-associative except for `**`. Prefix
+/// operators bind tighter than any binary operator except for `**`, so
+/// `-2 ** 2` is `-(2 ** 2)` while `-2 * 2` is `(-2) * 2`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl Parsable for Op {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        let op = Self::peek(p).ok_or_else(|| p.expected("operator"))?;
+        p.bump();
+
+        Ok(op)
+    }
+}
+
+impl Op {
+    const SYMBOLS: [(&'static str, Self); 11] = [
+        ("**", Self::Pow),
+        ("*", Self::Mul),
+        ("/", Self::Div),
+        ("%", Self::Rem),
+        ("+", Self::Add),
+        ("-", Self::Sub),
+        ("<<", Self::Shl),
+        (">>", Self::Shr),
+        ("&", Self::BitAnd),
+        ("^", Self::BitXor),
+        ("|", Self::BitOr),
+    ];
+
+    pub fn new(s: &str) -> Result<(&str, Self), String> {
+        <Self as Parsable>::new(s)
+    }
+
+    fn peek(p: &Parser) -> Option<Self> {
+        let token = p.peek().filter(|token| token.kind == TokenKind::Punct)?;
+        Self::from_symbol(token.text)
+    }
+
+    pub(crate) fn from_symbol(s: &str) -> Option<Self> {
+        Self::SYMBOLS
+            .iter()
+            .find(|(symbol, _)| *symbol == s)
+            .map(|(_, op)| *op)
+    }
+
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            Self::BitOr => 1,
+            Self::BitXor => 2,
+            Self::BitAnd => 3,
+            Self::Shl | Self::Shr => 4,
+            Self::Add | Self::Sub => 5,
+            Self::Mul | Self::Div | Self::Rem => 6,
+            Self::Pow => 7,
+        }
+    }
+
+    // `%` takes the Euclidean remainder, which is never negative, so that
+    // `n % 2` is either 0 or 1. Shifting by a negative amount or by 32 or more
+    // overflows, while bits shifted out of the number are dropped.
+    pub(crate) fn apply(self, lhs: i32, rhs: i32) -> Result<i32, EvalError> {
+        let res = match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+            Self::Div | Self::Rem if rhs == 0 => {
+                return Err(EvalError::DivisionByZero { op: self, lhs })
+            }
+            Self::Div => lhs.checked_div(rhs),
+            Self::Rem => lhs.checked_rem_euclid(rhs),
+            Self::Pow => match u32::try_from(rhs) {
+                Ok(rhs) => lhs.checked_pow(rhs),
+                Err(_) => return Err(EvalError::NegativeExponent { lhs, rhs }),
+            },
+            Self::BitAnd => Some(lhs & rhs),
+            Self::BitOr => Some(lhs | rhs),
+            Self::BitXor => Some(lhs ^ rhs),
+            Self::Shl => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)),
+            Self::Shr => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)),
+        };
+
+        res.ok_or(EvalError::Overflow { op: self, lhs, rhs })
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (symbol, _) = Self::SYMBOLS
+            .iter()
+            .find(|(_, op)| op == self)
+            .expect("every operator has a symbol");
+
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum UnaryOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
+impl UnaryOp {
+    fn eat(p: &mut Parser) -> Option<Self> {
+        let op = match p.peek() {
+            Some(token) if token.is(TokenKind::Punct, "-") => Self::Neg,
+            Some(token) if token.is(TokenKind::Punct, "!") => Self::Not,
+            Some(token) if token.is(TokenKind::Punct, "~") => Self::BitNot,
+            _ => return None,
+        };
+        p.bump();
+
+        Some(op)
+    }
+
+    // `!` is logical negation under the same truthiness as conditions: it
+    // turns zero into one and everything else into zero.
+    pub(crate) fn apply(self, operand: i32) -> Result<i32, EvalError> {
+        match self {
+            Self::Neg => operand
+                .checked_neg()
+                .ok_or(EvalError::NegationOverflow(operand)),
+            Self::Not => Ok((operand == 0) as i32),
+            Self::BitNot => Ok(!operand),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Expr {
+    Number(Number),
+    Operation {
+        lhs: Box<Self>,
+        rhs: Box<Self>,
+        op: Op,
+    },
+    Range {
+        start: Box<Self>,
+        end: Box<Self>,
+    },
+    FuncCall(FuncCall),
+    BindingUsage(BindingUsage),
+    Assignment(Assignment),
+    Block(Block),
+    List(List),
+    If(If),
+    While(While),
+    For(For),
+    Break(Option<Box<Self>>),
+    Continue,
+    Return(Option<Box<Self>>),
+    Ok(Box<Self>),
+    Err(Box<Self>),
+    Propagate(Box<Self>),
+    TryCatch(TryCatch),
+    Pipe {
+        arg: Box<Expr>,
+        call: FuncCall,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+}
+
+impl Parsable for Expr {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        utils::nested(|| {
+            let at_assignment = p.at_kind(TokenKind::Ident)
+                && p.nth(1)
+                    .is_some_and(|token| token.is(TokenKind::Punct, "="));
+            if at_assignment {
+                return Assignment::parse(p).map(Self::Assignment);
+            }
+
+            let checkpoint = p.checkpoint();
+            let expr = Self::parse_non_pipe(p)?;
+            Self::parse_pipes(p, checkpoint, expr)
+        })
+    }
+}
+
+impl Expr {
+    // Pipes have the lowest precedence and are left-associative, so
+    // `x |> f |> g 1` is `g 1 (f x)`.
+    fn parse_pipes(p: &mut Parser, checkpoint: Checkpoint, mut arg: Self) -> Result<Self, String> {
+        while p.at_punct("|>") {
+            utils::chain_link()?;
+            p.start_node_at(checkpoint, SyntaxKind::Pipe);
+            p.bump();
+            let call = FuncCall::parse_piped(p)?;
+            p.finish_node();
+
+            arg = Self::Pipe {
+                arg: Box::new(arg),
+                call,
+            };
+        }
+
+        Ok(arg)
+    }
+
+    fn parse_non_pipe(p: &mut Parser) -> Result<Self, String> {
+        let checkpoint = p.checkpoint();
+        let expr = Self::parse_operation(p)?;
+        Self::parse_range(p, checkpoint, expr)
+    }
+
+    // Prefix operators bind looser than function application, postfix `?`
+    // and `**`, but tighter than any other binary operator, so `-f x? * 2` is
+    // `(-((f x)?)) * 2` and `-x ** 2` is `-(x ** 2)`.
+    fn parse_non_operation(p: &mut Parser) -> Result<Self, String> {
+        let checkpoint = p.checkpoint();
+
+        if let Some(op) = UnaryOp::eat(p) {
+            // A minus directly followed by digits is a negative literal, so
+            // that the smallest number can be written down. That's unless the
+            // number is raised to a power, which comes first.
+            let at_pow = p
+                .nth(1)
+                .is_some_and(|token| token.is(TokenKind::Punct, "**"))
+                && !p.newline_before(1);
+            if op == UnaryOp::Neg && p.is_adjacent() && p.at_kind(TokenKind::Number) && !at_pow {
+                p.start_node_at(checkpoint, SyntaxKind::Number);
+                let number = Number::parse_with_sign(p, "-")?;
+                p.finish_node();
+
+                return Self::parse_postfix(p, checkpoint, Self::Number(number));
+            }
+
+            p.start_node_at(checkpoint, SyntaxKind::Unary);
+            let expr =
+                utils::nested(|| Self::parse_operation_binding_at_least(p, Op::Pow.precedence()))?;
+            p.finish_node();
+
+            return Ok(Self::Unary {
+                op,
+                expr: Box::new(expr),
+            });
+        }
+
+        let expr = Self::parse_primary(p)?;
+        Self::parse_postfix(p, checkpoint, expr)
+    }
+
+    fn parse_postfix(p: &mut Parser, checkpoint: Checkpoint, expr: Self) -> Result<Self, String> {
+        if p.is_adjacent() && p.at_punct("?") {
+            p.start_node_at(checkpoint, SyntaxKind::Propagate);
+            p.bump();
+            p.finish_node();
+
+            utils::nested(|| Self::parse_postfix(p, checkpoint, Self::Propagate(Box::new(expr))))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    fn parse_primary(p: &mut Parser) -> Result<Self, String> {
+        let (kind, text) = match p.peek() {
+            Some(token) => (token.kind, token.text),
+            None => return Err(p.expected("expression")),
+        };
+
+        match (kind, text) {
+            (TokenKind::Keyword, "if") => If::parse(p).map(Self::If),
+            (TokenKind::Keyword, "while") => While::parse(p).map(Self::While),
+            (TokenKind::Keyword, "for") => For::parse(p).map(Self::For),
+            (TokenKind::Keyword, "break") => p.node(SyntaxKind::Break, |p| {
+                p.bump();
+                Self::parse_jump_value(p).map(Self::Break)
+            }),
+            (TokenKind::Keyword, "continue") => p.node(SyntaxKind::Continue, |p| {
+                p.bump();
+                Ok(Self::Continue)
+            }),
+            (TokenKind::Keyword, "return") => p.node(SyntaxKind::Return, |p| {
+                p.bump();
+                Self::parse_jump_value(p).map(Self::Return)
+            }),
+            (TokenKind::Keyword, "Ok" | "Err") => Self::parse_result(p),
+            (TokenKind::Keyword, "try") => TryCatch::parse(p).map(Self::TryCatch),
+            (TokenKind::Ident, _) if Self::at_atom(p, 1) => FuncCall::parse(p).map(Self::FuncCall),
+            (TokenKind::Punct, "{") => Block::parse(p).map(Self::Block),
+            _ => Self::parse_atom(p),
+        }
+    }
+
+    // Whether the `n`th token starts an atom on the same line.
+    pub(crate) fn at_atom(p: &Parser, n: usize) -> bool {
+        let starts_atom = p.nth(n).is_some_and(|token| {
+            matches!(token.kind, TokenKind::Ident | TokenKind::Number)
+                || token.is(TokenKind::Punct, "[")
+                || token.is(TokenKind::Punct, "(")
+        });
+
+        starts_atom && !p.newline_before(n)
+    }
+
+    pub(crate) fn parse_atom(p: &mut Parser) -> Result<Self, String> {
+        match p.peek().map(|token| (token.kind, token.text)) {
+            Some((TokenKind::Ident, _)) => BindingUsage::parse(p).map(Self::BindingUsage),
+            Some((TokenKind::Number, _)) => Number::parse(p).map(Self::Number),
+            Some((TokenKind::Punct, "[")) => List::parse(p).map(Self::List),
+            Some((TokenKind::Punct, "(")) => Self::parse_paren(p),
+            _ => Err(p.expected("expression")),
+        }
+    }
+
+    fn parse_paren(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Paren, |p| {
+            p.expect_punct("(")?;
+            let expr = Self::parse(p)?;
+            p.expect_punct(")")?;
+
+            Ok(expr)
+        })
+    }
+
+    fn parse_operation(p: &mut Parser) -> Result<Self, String> {
+        Self::parse_operation_binding_at_least(p, 0)
+    }
+
+    // Parses operations whose operators bind at least as tightly as
+    // `min_precedence`, leaving looser ones to the caller. Operators are
+    // chained in a loop, so only a right operand counts as a level of
+    // nesting.
+    fn parse_operation_binding_at_least(
+        p: &mut Parser,
+        min_precedence: u8,
+    ) -> Result<Self, String> {
+        let checkpoint = p.checkpoint();
+        let mut lhs = Self::parse_non_operation(p)?;
+
+        loop {
+            let op = match Op::peek(p) {
+                Some(op) if op.precedence() >= min_precedence && !p.newline_before(0) => op,
+                _ => return Ok(lhs),
+            };
+            utils::chain_link()?;
+            p.start_node_at(checkpoint, SyntaxKind::Operation);
+            p.bump();
+
+            let rhs_min_precedence = if op == Op::Pow {
+                op.precedence()
+            } else {
+                op.precedence() + 1
+            };
+            let rhs =
+                utils::nested(|| Self::parse_operation_binding_at_least(p, rhs_min_precedence))?;
+            p.finish_node();
+
+            lhs = Self::Operation {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                op,
+            };
+        }
+    }
+
+    fn parse_range(p: &mut Parser, checkpoint: Checkpoint, start: Self) -> Result<Self, String> {
+        if p.newline_before(0) || !p.at_punct("..") {
+            return Ok(start);
+        }
+        p.start_node_at(checkpoint, SyntaxKind::Range);
+        p.bump();
+        let end = Self::parse_operation(p)?;
+        p.finish_node();
+
+        Ok(Self::Range {
+            start: Box::new(start),
+            end: Box::new(end),
+        })
+    }
+
+    fn parse_result(p: &mut Parser) -> Result<Self, String> {
+        let is_ok = p.at_keyword("Ok");
+        let kind = if is_ok {
+            SyntaxKind::Ok
+        } else {
+            SyntaxKind::Err
+        };
+
+        p.node(kind, |p| {
+            p.bump();
+
+            if p.newline_before(0) {
+                return Err(p.expected("expression"));
+            }
+            let val = Box::new(Self::parse(p)?);
+
+            Ok(if is_ok { Self::Ok(val) } else { Self::Err(val) })
+        })
+    }
+
+    fn parse_jump_value(p: &mut Parser) -> Result<Option<Box<Self>>, String> {
+        // The value has to start on the same line, otherwise the next
+        // statement in the block would be swallowed by the `break` or `return`.
+        if p.newline_before(0) || p.at_punct("}") || p.at_punct("]") || p.at_punct(",") {
+            return Ok(None);
+        }
+
+        Self::parse(p).map(|val| Some(Box::new(val)))
+    }
+
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        env.consume_fuel()?;
+        let _eval_guard = env.enter_eval()?;
+        self.eval_fueled(env)
+    }
+
+    // Evaluates the expression once the fuel for it has been consumed. Every
+    // level of nesting takes a frame of this function, so arms that do more
+    // than dispatch call out to a helper to keep that frame small.
+    fn eval_fueled(&self, env: &Env) -> Result<Val, Unwind> {
+        match self {
+            Self::Number(Number(number)) => Ok(Val::Number(*number)),
+            Self::Operation { .. } => self.eval_operation(env),
+            Self::Range { start, end } => Self::eval_range(start, end, env),
+            Self::FuncCall(func_call) => func_call.eval(env),
+            Self::BindingUsage(binding_usage) => binding_usage.eval(env),
+            Self::Assignment(assignment) => assignment.eval(env),
+            Self::Block(block) => block.eval(env),
+            Self::List(list) => list.eval(env),
+            Self::If(if_else) => if_else.eval(env),
+            Self::While(while_loop) => while_loop.eval(env),
+            Self::For(for_loop) => for_loop.eval(env),
+            Self::Break(val) => Self::eval_jump(val, env, Unwind::Break),
+            Self::Continue => Err(Unwind::Continue),
+            Self::Return(val) => Self::eval_jump(val, env, Unwind::Return),
+            Self::Ok(val) => Self::eval_result(val, env, Val::Ok),
+            Self::Err(val) => Self::eval_result(val, env, Val::Err),
+            Self::Propagate(expr) => Self::eval_propagate(expr, env),
+            Self::TryCatch(try_catch) => try_catch.eval(env),
+            Self::Pipe { .. } => self.eval_pipes(env),
+            Self::Unary { op, expr } => Self::eval_unary(*op, expr, env),
+        }
+    }
+
+    // Evaluates the expression as the last thing its function does, leaving
+    // any call it would make for the function to make instead.
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Unwind> {
+        env.consume_fuel()?;
+        let _eval_guard = env.enter_eval()?;
+
+        match self {
+            Self::FuncCall(func_call) => func_call.eval_tail(env),
+            Self::BindingUsage(binding_usage) => binding_usage.eval_tail(env),
+            Self::Block(block) => block.eval_tail(env),
+            Self::If(if_else) => if_else.eval_tail(env),
+            Self::Return(Some(val)) => val.eval_tail(env),
+            _ => self.eval_fueled(env).map(Tail::Val),
+        }
+    }
+
+    // Long chains of operations and pipes make for a deep left spine, which
+    // is walked in a loop instead of recursively so that it can't overflow
+    // the stack.
+    fn eval_operation(&self, env: &Env) -> Result<Val, Unwind> {
+        let mut operations = Vec::new();
+        let mut lhs = self;
+        while let Self::Operation { lhs: next, rhs, op } = lhs {
+            operations.push((rhs, *op));
+            lhs = next;
+        }
+        // The first operation's fuel was consumed by `eval`.
+        for _ in 1..operations.len() {
+            env.consume_fuel()?;
+        }
+
+        let mut val = lhs.eval(env)?;
+        for (rhs, op) in operations.into_iter().rev() {
+            val = Self::apply_op(op, val, rhs.eval(env)?)?;
+        }
+
+        Ok(val)
+    }
+
+    fn apply_op(op: Op, lhs: Val, rhs: Val) -> Result<Val, Unwind> {
+        match (lhs, rhs) {
+            (Val::Number(lhs), Val::Number(rhs)) => Ok(Val::Number(op.apply(lhs, rhs)?)),
+            _ => Err(Unwind::Error(EvalError::NonNumberOperands)),
+        }
+    }
+
+    fn eval_pipes(&self, env: &Env) -> Result<Val, Unwind> {
+        let mut calls = Vec::new();
+        let mut arg = self;
+        while let Self::Pipe { arg: next, call } = arg {
+            calls.push(call);
+            arg = next;
+        }
+        for _ in 1..calls.len() {
+            env.consume_fuel()?;
+        }
+
+        let mut val = arg.eval(env)?;
+        for call in calls.into_iter().rev() {
+            val = call.eval_piped(env, val)?;
+        }
+
+        Ok(val)
+    }
+
+    fn eval_range(start: &Self, end: &Self, env: &Env) -> Result<Val, Unwind> {
+        match (start.eval(env)?, end.eval(env)?) {
+            (Val::Number(start), Val::Number(end)) => Ok(Val::Range { start, end }),
+            _ => Err(Unwind::Error(EvalError::NonNumberRangeBounds)),
+        }
+    }
+
+    fn eval_jump(
+        val: &Option<Box<Self>>,
+        env: &Env,
+        jump: fn(Val) -> Unwind,
+    ) -> Result<Val, Unwind> {
+        let val = match val {
+            Some(val) => val.eval(env)?,
+            None => Val::Unit,
+        };
+
+        Err(jump(val))
+    }
+
+    fn eval_result(val: &Self, env: &Env, result: fn(Box<Val>) -> Val) -> Result<Val, Unwind> {
+        Ok(val.eval(env)?.wrap(result)?)
+    }
+
+    fn eval_unary(op: UnaryOp, expr: &Self, env: &Env) -> Result<Val, Unwind> {
+        match expr.eval(env)? {
+            Val::Number(operand) => Ok(Val::Number(op.apply(operand)?)),
+            _ => Err(Unwind::Error(EvalError::NonNumberOperands)),
+        }
+    }
+
+    fn eval_propagate(expr: &Self, env: &Env) -> Result<Val, Unwind> {
+        match expr.eval(env)? {
+            Val::Ok(val) => Ok(*val),
+            Val::Err(val) => Err(Unwind::Propagate(Val::Err(val))),
+            val => Err(Unwind::Error(EvalError::NotResult(val))),
         }
     }
 }
@@ -0,0 +1,120 @@
+use crate::env::Env;
+use crate::expr::Expr;
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Assignment {
+    pub(crate) name: String,
+    pub(crate) val: Box<Expr>,
+}
+
+impl Parsable for Assignment {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Assignment, |p| {
+            let name = p.expect_ident()?.to_owned();
+            p.expect_punct("=")?;
+
+            let val = Expr::parse(p)?;
+
+            Ok(Self {
+                name,
+                val: Box::new(val),
+            })
+        })
+    }
+}
+
+impl Assignment {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        let val = self.val.eval(env)?;
+        env.assign_binding(&self.name, val)?;
+
+        Ok(Val::Unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::expr::{BindingUsage, Number, Op};
+
+    #[test]
+    fn parse_assignment() {
+        assert_eq!(
+            Assignment::new("x = x + 1"),
+            Ok((
+                "",
+                Assignment {
+                    name: "x".to_owned(),
+                    val: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_owned()
+                        })),
+                        rhs: Box::new(Expr::Number(Number(1))),
+                        op: Op::Add,
+                    }),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn cannot_parse_fat_arrow_as_assignment() {
+        assert_eq!(
+            Assignment::new("x => 1"),
+            Err("expected `=` after `x`".to_owned())
+        );
+    }
+
+    #[test]
+    fn eval_assignment_to_mutable_binding() {
+        let mut env = Env::default();
+        env.store_mutable_binding("x".to_owned(), Val::Number(1))
+            .unwrap();
+
+        assert_eq!(
+            Assignment {
+                name: "x".to_owned(),
+                val: Box::new(Expr::Number(Number(5))),
+            }
+            .eval(&env),
+            Ok(Val::Unit)
+        );
+        assert_eq!(env.get_binding("x"), Ok(Val::Number(5)));
+    }
+
+    #[test]
+    fn eval_assignment_to_immutable_binding() {
+        let mut env = Env::default();
+        env.store_binding("x".to_owned(), Val::Number(1)).unwrap();
+
+        assert_eq!(
+            Assignment {
+                name: "x".to_owned(),
+                val: Box::new(Expr::Number(Number(5))),
+            }
+            .eval(&env),
+            Err(Unwind::Error(EvalError::AssignToImmutableBinding(
+                "x".to_owned()
+            )))
+        );
+    }
+
+    #[test]
+    fn eval_assignment_to_non_existent_binding() {
+        assert_eq!(
+            Assignment {
+                name: "x".to_owned(),
+                val: Box::new(Expr::Number(Number(5))),
+            }
+            .eval(&Env::default()),
+            Err(Unwind::Error(EvalError::BindingDoesNotExist(
+                "x".to_owned()
+            )))
+        );
+    }
+}
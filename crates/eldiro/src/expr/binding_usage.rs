@@ -1,11 +1,15 @@
 use crate::env::Env;
-use crate::utils;
+use crate::expr::{FuncCall, Tail};
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
 use crate::val::Val;
-use crate::expr::FuncCall;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::EvalError;
+
     #[test]
     fn parse_binding_usage() {
         assert_eq!(
@@ -22,7 +26,8 @@ mod tests {
     #[test]
     fn eval_existing_binding_usage() {
         let mut env = Env::default();
-        env.store_binding("foo".to_string(), Val::Number(3));
+        env.store_binding("foo".to_string(), Val::Number(3))
+            .unwrap();
 
         assert_eq! {
             BindingUsage {
@@ -40,7 +45,7 @@ mod tests {
             BindingUsage {
                 name: "foo".to_owned()
             }.eval(&env),
-            Err("binding with name 'foo' does not exist".to_owned())
+            Err(Unwind::Error(EvalError::BindingDoesNotExist("foo".to_owned())))
         }
     }
 }
@@ -50,26 +55,38 @@ pub struct BindingUsage {
     pub(crate) name: String,
 }
 
+impl Parsable for BindingUsage {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::BindingUsage, |p| {
+            let name = p.expect_ident()?.to_owned();
+            Ok(Self { name })
+        })
+    }
+}
+
 impl BindingUsage {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, name) = utils::extract_ident(s)?;
-        Ok((
-            s,
-            BindingUsage {
-                name: name.to_owned(),
-            },
-        ))
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Unwind> {
+        match env.get_binding(&self.name) {
+            Ok(val) => Ok(Tail::Val(val)),
+            Err(_) if env.get_func(&self.name).is_ok() => FuncCall {
+                callee: self.name.clone(),
+                params: vec![],
+            }
+            .eval_tail(env),
+            Err(error) => Err(Unwind::Error(error)),
+        }
     }
 
-    pub(crate) fn eval(&self, env: &Env) -> Result<Val, String> {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
         env.get_binding(&self.name).or_else(|error_msg| {
             if env.get_func(&self.name).is_ok() {
                 FuncCall {
                     callee: self.name.clone(),
-                    params: vec![]
-                }.eval(env)
+                    params: vec![],
+                }
+                .eval(env)
             } else {
-                Err(error_msg)
+                Err(Unwind::Error(error_msg))
             }
         })
     }
@@ -1,7 +1,9 @@
 use crate::env::Env;
-use crate::expr::Op;
+use crate::expr::Tail;
+use crate::parser::{Parsable, Parser};
 use crate::stmt::Stmt;
-use crate::utils;
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
 use crate::val::Val;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -9,37 +11,57 @@ pub(crate) struct Block {
     pub(crate) stmts: Vec<Stmt>,
 }
 
-impl Block {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let s = utils::tag("{", s)?;
-        let (s, _) = utils::extract_whitespace(s);
+impl Parsable for Block {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Block, |p| {
+            p.expect_punct("{")?;
 
-        let (s, stmts) = utils::sequence(Stmt::new, utils::extract_whitespace, s)?;
+            let mut stmts = Vec::new();
+            while !p.at_punct("}") && !p.at_end() {
+                stmts.extend(p.recover(Stmt::parse));
+            }
 
-        let (s, _) = utils::extract_whitespace(s);
-        let s = utils::tag("}", s)?;
+            p.expect_punct("}")?;
 
-        Ok((s, Self { stmts }))
+            Ok(Self { stmts })
+        })
     }
+}
 
-    pub(crate) fn eval(&self, env: &Env) -> Result<Val, String> {
-        if self.stmts.is_empty() {
-            return Ok(Val::Unit);
-        }
+impl Block {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        self.eval_with(env, Val::Unit, |stmt, env| stmt.eval(env))
+    }
+
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Unwind> {
+        self.eval_with(env, Tail::Val(Val::Unit), |stmt, env| stmt.eval_tail(env))
+    }
+
+    // Evaluates every statement but the last one in a new scope, and then
+    // the last one with `eval_last`.
+    fn eval_with<T>(
+        &self,
+        env: &Env,
+        empty: T,
+        eval_last: impl FnOnce(&Stmt, &mut Env) -> Result<T, Unwind>,
+    ) -> Result<T, Unwind> {
+        let (last, stmts_except_last_one) = match self.stmts.split_last() {
+            Some(split) => split,
+            None => return Ok(empty),
+        };
+
+        let _eval_guard = env.enter_eval()?;
         let mut env = env.create_child();
-        let stmts_except_last_one = &self.stmts[..self.stmts.len() - 1];
         for stmt in stmts_except_last_one {
             stmt.eval(&mut env)?;
         }
-        self.stmts
-            .last()
-            .map_or(Ok(Val::Unit), |stmt| stmt.eval(&mut env))
+        eval_last(last, &mut env)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::{BindingUsage, Expr, Number};
+    use super::super::{BindingUsage, Expr, Number, Op};
     use super::*;
     use crate::binding_def::BindingDef;
 
@@ -77,13 +99,15 @@ mod tests {
                     stmts: vec![
                         Stmt::BindingDef(BindingDef {
                             name: "a".to_owned(),
-                            val: Expr::Number(Number(10))
+                            val: Expr::Number(Number(10)),
+                            mutable: false,
                         }),
                         Stmt::BindingDef(BindingDef {
                             name: "b".to_owned(),
                             val: Expr::BindingUsage(BindingUsage {
                                 name: "a".to_owned()
-                            })
+                            }),
+                            mutable: false,
                         }),
                         Stmt::Expr(Expr::BindingUsage(BindingUsage {
                             name: "b".to_owned()
@@ -120,6 +144,7 @@ mod tests {
                     Stmt::BindingDef(BindingDef {
                         name: "foo".to_owned(),
                         val: Expr::Number(Number(3)),
+                        mutable: false,
                     }),
                     Stmt::Expr(Expr::BindingUsage(BindingUsage {
                         name: "foo".to_owned()
@@ -138,19 +163,22 @@ mod tests {
                 stmts: vec![
                     Stmt::BindingDef(BindingDef {
                         name: "foo".to_owned(),
-                        val: Expr::Number(Number(3))
+                        val: Expr::Number(Number(3)),
+                        mutable: false,
                     }),
                     Stmt::BindingDef(BindingDef {
                         name: "bar".to_owned(),
                         val: Expr::Number(Number(4)),
+                        mutable: false,
                     }),
                     Stmt::BindingDef(BindingDef {
                         name: "foobar".to_owned(),
                         val: Expr::Number(Number(5)),
+                        mutable: false,
                     })
                 ],
             }
-            .eval(&mut Env::default()),
+            .eval(&Env::default()),
             Ok(Val::Unit)
         );
     }
@@ -182,6 +210,7 @@ mod tests {
                     Stmt::BindingDef(BindingDef {
                         name: "foo".to_owned(),
                         val: Expr::Number(Number(3)),
+                        mutable: false,
                     }),
                     Stmt::Expr(Expr::Block(Block {
                         stmts: vec![Stmt::Expr(Expr::BindingUsage(BindingUsage {
@@ -0,0 +1,137 @@
+use crate::env::Env;
+use crate::expr::{Block, Expr};
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct For {
+    pub(crate) binding: String,
+    pub(crate) iterable: Box<Expr>,
+    pub(crate) body: Block,
+}
+
+impl Parsable for For {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::For, |p| {
+            p.expect_keyword("for")?;
+            let binding = p.expect_ident()?.to_owned();
+
+            p.expect_keyword("in")?;
+            let iterable = Expr::parse(p)?;
+
+            let body = Block::parse(p)?;
+
+            Ok(Self {
+                binding,
+                iterable: Box::new(iterable),
+                body,
+            })
+        })
+    }
+}
+
+impl For {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        for item in self.iterable.eval(env)?.into_items()? {
+            // An empty body doesn't evaluate any expressions, so iterating
+            // has to consume fuel by itself.
+            env.consume_fuel()?;
+
+            let mut child_env = env.create_child();
+            child_env.store_binding(self.binding.clone(), item)?;
+
+            match self.body.eval(&child_env) {
+                Ok(_) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break(val)) => return Ok(val),
+                Err(unwind) => return Err(unwind),
+            }
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::expr::{BindingUsage, Number};
+    use crate::limits::Limits;
+    use crate::stmt::Stmt;
+
+    #[test]
+    fn parse_for_over_range() {
+        assert_eq!(
+            For::new("for i in 0..3 {}"),
+            Ok((
+                "",
+                For {
+                    binding: "i".to_owned(),
+                    iterable: Box::new(Expr::Range {
+                        start: Box::new(Expr::Number(Number(0))),
+                        end: Box::new(Expr::Number(Number(3))),
+                    }),
+                    body: Block { stmts: vec![] },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_for_breaking_with_loop_variable() {
+        assert_eq!(
+            For {
+                binding: "x".to_owned(),
+                iterable: Box::new(Expr::Range {
+                    start: Box::new(Expr::Number(Number(5))),
+                    end: Box::new(Expr::Number(Number(10))),
+                }),
+                body: Block {
+                    stmts: vec![Stmt::Expr(Expr::Break(Some(Box::new(Expr::BindingUsage(
+                        BindingUsage {
+                            name: "x".to_owned()
+                        }
+                    )))))]
+                },
+            }
+            .eval(&Env::default()),
+            Ok(Val::Number(5))
+        );
+    }
+
+    #[test]
+    fn eval_for_with_empty_body_consumes_fuel() {
+        let env = Env::with_limits(Limits {
+            fuel: Some(1000),
+            ..Limits::default()
+        });
+
+        assert_eq!(
+            For {
+                binding: "x".to_owned(),
+                iterable: Box::new(Expr::Range {
+                    start: Box::new(Expr::Number(Number(0))),
+                    end: Box::new(Expr::Number(Number(i32::MAX))),
+                }),
+                body: Block { stmts: vec![] },
+            }
+            .eval(&env),
+            Err(Unwind::Error(EvalError::FuelExhausted { fuel: 1000 }))
+        );
+    }
+
+    #[test]
+    fn eval_for_over_number() {
+        assert_eq!(
+            For {
+                binding: "x".to_owned(),
+                iterable: Box::new(Expr::Number(Number(5))),
+                body: Block { stmts: vec![] },
+            }
+            .eval(&Env::default()),
+            Err(Unwind::Error(EvalError::NotIterable(Val::Number(5))))
+        );
+    }
+}
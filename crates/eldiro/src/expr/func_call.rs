@@ -1,14 +1,17 @@
-use crate::utils;
-use crate::val::Val;
-use crate::{
-    expr::{Expr, Number},
-    Env,
-};
+use crate::error::EvalError;
+use crate::expr::Expr;
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::{Func, List, Val};
+use crate::Env;
 
 #[cfg(test)]
 mod tests {
-    use crate::expr::BindingUsage;
+    use crate::expr::{BindingUsage, Block, Number};
+    use crate::limits::Limits;
     use crate::stmt::Stmt;
+    use crate::val::Func;
 
     use super::*;
 
@@ -22,7 +25,8 @@ mod tests {
             Stmt::Expr(Expr::BindingUsage(BindingUsage {
                 name: "x".to_owned(),
             })),
-        );
+        )
+        .unwrap();
 
         assert_eq!(
             FuncCall {
@@ -44,12 +48,14 @@ mod tests {
                 params: vec![]
             }
             .eval(&env),
-            Err("function with name 'i_dont_exist' does not exist.".to_owned())
+            Err(Unwind::Error(EvalError::FuncDoesNotExist(
+                "i_dont_exist".to_owned()
+            )))
         );
     }
 
     #[test]
-    fn eval_func_call_with_too_few_parameters() {
+    fn eval_func_call_with_too_few_parameters_applies_partially() {
         let mut env = Env::default();
 
         env.store_func(
@@ -64,7 +70,8 @@ mod tests {
                 })),
                 op: crate::expr::Op::Mul,
             }),
-        );
+        )
+        .unwrap();
 
         assert_eq!(
             FuncCall {
@@ -72,7 +79,308 @@ mod tests {
                 params: vec![Expr::Number(Number(100))]
             }
             .eval(&env),
-            Err("expected 2 parameters, got 1".to_owned())
+            Ok(Val::Func(Box::new(Func {
+                args: vec![Val::Number(100)].into(),
+                ..env.get_func("mul").unwrap()
+            })))
+        );
+    }
+
+    fn eval(s: &str) -> Result<Val, EvalError> {
+        let parse = crate::parse(s);
+        assert_eq!(parse.errors(), &[]);
+
+        parse.eval(&mut Env::default())
+    }
+
+    #[test]
+    fn eval_partially_applied_func() {
+        assert_eq!(
+            eval("fn add x y => x + y\nlet inc = add 1\ninc 2"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_func_used_as_value() {
+        assert_eq!(
+            eval("fn add x y => x + y\nlet plus = add\nlet inc = plus 1\ninc 2"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_func_passed_to_func() {
+        assert_eq!(
+            eval("fn add x y => x + y\nfn apply f x => f x\nlet inc = add 1\nlet applied = apply inc\napplied 2"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_with_extra_args_for_returned_func() {
+        assert_eq!(
+            eval("fn add x y => x + y\nfn adder x => add x\nadder 1 2"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_with_too_many_parameters() {
+        assert_eq!(
+            eval("fn add x y => x + y\nadd 1 2 3"),
+            Err(EvalError::WrongNumberOfParams {
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+
+    #[test]
+    fn eval_local_func_passed_to_func() {
+        assert_eq!(
+            eval("fn apply f x => f x\nfn outer x => {\n fn inner y => x + y\n apply inner 1\n}\nouter 10"),
+            Ok(Val::Number(11))
+        );
+    }
+
+    #[test]
+    fn eval_partially_applied_func_before_its_definition() {
+        assert_eq!(
+            eval("let inc = add 1\nfn add x y => x + y\ninc 2"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_func_value_taken_before_redefinition() {
+        assert_eq!(
+            eval("fn f x => x\nlet g = f\nfn f x => x + 1\ng 1"),
+            Ok(Val::Number(1))
+        );
+    }
+
+    #[test]
+    fn eval_partially_applied_func_outside_of_its_scope() {
+        assert_eq!(
+            eval("let inc = { fn add x y => x + y\n add 1 }\ninc 2"),
+            Err(EvalError::FuncOutOfScope("add".to_owned()))
+        );
+    }
+
+    #[test]
+    fn parse_func_call_followed_by_block() {
+        assert_eq!(
+            FuncCall::new("f x { 1 }"),
+            Ok((
+                " { 1 }",
+                FuncCall {
+                    callee: "f".to_owned(),
+                    params: vec![Expr::BindingUsage(BindingUsage {
+                        name: "x".to_owned()
+                    })]
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_with_early_return_from_loop() {
+        let mut env = Env::default();
+
+        let (_, body) = Stmt::new(
+            "{
+    for x in 1..10 {
+        if x - 3 {} else { return x * 2 }
+    }
+    0
+}",
+        )
+        .unwrap();
+        env.store_func("find".to_owned(), vec![], body).unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "find".to_owned(),
+                params: vec![]
+            }
+            .eval(&env),
+            Ok(Val::Number(6))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_propagating_err() {
+        let mut env = Env::default();
+
+        let (_, body) = Stmt::new(
+            "{
+    let y = x?
+    Ok y + 1
+}",
+        )
+        .unwrap();
+        env.store_func("inc".to_owned(), vec!["x".to_owned()], body)
+            .unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "inc".to_owned(),
+                params: vec![Expr::Ok(Box::new(Expr::Number(Number(1))))]
+            }
+            .eval(&env),
+            Ok(Val::Ok(Box::new(Val::Number(2))))
+        );
+        assert_eq!(
+            FuncCall {
+                callee: "inc".to_owned(),
+                params: vec![Expr::Err(Box::new(Expr::Number(Number(5))))]
+            }
+            .eval(&env),
+            Ok(Val::Err(Box::new(Val::Number(5))))
+        );
+    }
+
+    #[test]
+    fn eval_infinitely_recursive_func_call() {
+        let mut env = Env::with_limits(Limits {
+            max_call_depth: 50,
+            ..Limits::default()
+        });
+
+        let (_, body) = Stmt::new("1 + forever").unwrap();
+        env.store_func("forever".to_owned(), vec![], body).unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "forever".to_owned(),
+                params: vec![]
+            }
+            .eval(&env),
+            Err(Unwind::Error(EvalError::StackOverflow {
+                max_call_depth: 50
+            }))
+        );
+    }
+
+    #[test]
+    fn eval_infinitely_recursive_func_call_with_default_limits() {
+        let mut env = Env::default();
+
+        let (_, body) = Stmt::new("{ let x = 1\n if x { x + forever } else { 0 } }").unwrap();
+        env.store_func("forever".to_owned(), vec![], body).unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "forever".to_owned(),
+                params: vec![]
+            }
+            .eval(&env),
+            Err(Unwind::Error(EvalError::StackOverflow {
+                max_call_depth: Limits::default().max_call_depth
+            }))
+        );
+    }
+
+    #[test]
+    fn eval_deeply_nested_recursion_without_overflowing_native_stack() {
+        // Every call is nested fifty blocks deep, so the native stack runs
+        // out long before the call depth limit is reached.
+        let s = format!(
+            "fn f n => if n {{ 1 + {}f (n - 1) {}}} else {{ 0 }}\nf 127",
+            "{ ".repeat(50),
+            "} ".repeat(50)
+        );
+
+        // The default limits are meant to fit in the stack spawned threads
+        // get by default.
+        let result = std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(move || {
+                eval(&s)
+                    .map(|val| val.to_string())
+                    .map_err(|err| err.to_string())
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            result,
+            Err(EvalError::StackExhausted {
+                max_stack_bytes: Limits::default().max_stack_bytes
+            }
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn eval_self_recursive_tail_call_in_constant_stack_space() {
+        assert_eq!(
+            eval("fn count n => if n { count (n - 1) } else { 0 }\ncount 1000000"),
+            Ok(Val::Number(0))
+        );
+    }
+
+    #[test]
+    fn eval_mutually_recursive_tail_calls_in_constant_stack_space() {
+        assert_eq!(
+            eval(
+                "fn even n => if n { return odd (n - 1) } else { 1 }
+fn odd n => {
+    let m = n
+    if m { even (m - 1) } else { 0 }
+}
+even 1000000"
+            ),
+            Ok(Val::Number(1))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_args_in_caller_env() {
+        let mut env = Env::default();
+
+        let (_, body) = Stmt::new("x + y").unwrap();
+        env.store_func("add".to_owned(), vec!["x".to_owned(), "y".to_owned()], body)
+            .unwrap();
+        env.store_binding("x".to_owned(), Val::Number(10)).unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "add".to_owned(),
+                params: vec![
+                    Expr::Number(Number(1)),
+                    Expr::BindingUsage(BindingUsage {
+                        name: "x".to_owned()
+                    })
+                ]
+            }
+            .eval(&env),
+            Ok(Val::Number(11))
+        );
+    }
+
+    #[test]
+    fn eval_func_call_with_break_in_body() {
+        let mut env = Env::default();
+
+        env.store_func(
+            "escape".to_owned(),
+            vec![],
+            Stmt::Expr(Expr::Block(Block {
+                stmts: vec![Stmt::Expr(Expr::Break(None))],
+            })),
+        )
+        .unwrap();
+
+        assert_eq!(
+            FuncCall {
+                callee: "escape".to_owned(),
+                params: vec![]
+            }
+            .eval(&env),
+            Err(Unwind::Error(EvalError::BreakOutsideLoop))
         );
     }
 }
@@ -83,42 +391,196 @@ pub(crate) struct FuncCall {
     pub(crate) params: Vec<Expr>,
 }
 
+impl Parsable for FuncCall {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::FuncCall, |p| {
+            let callee = p.expect_ident()?.to_owned();
+
+            let params = Self::parse_args(p)?;
+            if params.is_empty() {
+                return Err(p.expected("argument"));
+            }
+
+            Ok(Self { callee, params })
+        })
+    }
+}
+
 impl FuncCall {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, callee) = utils::extract_ident(s)?;
-        let (s, _) = utils::take_while(|c| c == ' ', s);
+    // The right-hand side of a pipe, which unlike a normal call can have no
+    // arguments, since the piped value is passed as the last one.
+    pub(crate) fn parse_piped(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::FuncCall, |p| {
+            let callee = p.expect_ident()?.to_owned();
+            let params = Self::parse_args(p)?;
 
-        let (s, params) = utils::sequence1(Expr::new, |s| utils::take_while(|c| c == ' ', s), s)?;
+            Ok(Self { callee, params })
+        })
+    }
 
-        Ok((
-            s,
-            Self {
-                callee: callee.to_owned(),
-                params,
-            },
-        ))
+    // Arguments are restricted to atoms so that function application binds
+    // tighter than any operator: `f x + 1` is `(f x) + 1`, and `f g x` passes
+    // `g` itself. Anything else has to be parenthesized, as in
+    // `f (g x) (1 + 2)`. Blocks aren't atoms either, so that `while f x { ... }`
+    // treats the block as the loop body rather than as another argument.
+    fn parse_args(p: &mut Parser) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        while Expr::at_atom(p, 0) {
+            args.push(Expr::parse_atom(p)?);
+        }
+
+        Ok(args)
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        Tail::Call {
+            callee: self.callee.clone(),
+            args: self.eval_args(env)?,
+        }
+        .finish(env)
     }
 
-    pub(super) fn eval(&self, env: &Env) -> Result<Val, String> {
-        let mut child_env = env.create_child();
+    pub(crate) fn eval_piped(&self, env: &Env, piped: Val) -> Result<Val, Unwind> {
+        let mut args = self.eval_args(env)?;
+        args.push(piped);
+
+        Tail::Call {
+            callee: self.callee.clone(),
+            args,
+        }
+        .finish(env)
+    }
 
-        let (param_names, body) = env.get_func(&self.callee)?;
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Unwind> {
+        // A function defined inside the current call is gone once the call
+        // returns, so it has to be called right away, and so does any call
+        // it's passed to.
+        if env.is_call_local(&self.callee) {
+            return self.eval(env).map(Tail::Val);
+        }
 
-        let num_expected_params = param_names.len();
-        let num_acutal_params = self.params.len();
+        let args = self.eval_args(env)?;
+        let passes_call_local_func = args
+            .iter()
+            .any(|arg| arg.any_func(&|func| env.is_call_local_func(func)));
 
-        if num_expected_params != num_acutal_params {
-            return Err(format!(
-                "expected {} parameters, got {}",
-                num_expected_params, num_acutal_params
-            ));
+        let tail = Tail::Call {
+            callee: self.callee.clone(),
+            args,
+        };
+        if passes_call_local_func {
+            tail.finish(env).map(Tail::Val)
+        } else {
+            Ok(tail)
         }
+    }
 
-        for (param_name, param_expr) in param_names.into_iter().zip(&self.params) {
-            let param_val = param_expr.eval(&child_env)?;
-            child_env.store_binding(param_name, param_val);
+    fn eval_args(&self, env: &Env) -> Result<Vec<Val>, Unwind> {
+        // A plain loop rather than `collect`, which takes a lot more stack
+        // in a debug build.
+        let mut args = Vec::with_capacity(self.params.len());
+        for param_expr in &self.params {
+            args.push(param_expr.eval(env)?);
         }
 
-        body.eval(&mut child_env)
+        Ok(args)
+    }
+}
+
+// The result of evaluating an expression in tail position. Rather than being
+// made right away, a call in tail position is handed back to the call whose
+// body it's in, which then makes it in a loop so that the stack doesn't grow.
+#[derive(Debug)]
+pub(crate) enum Tail {
+    Val(Val),
+    Call { callee: String, args: Vec<Val> },
+}
+
+impl Tail {
+    pub(crate) fn finish(self, env: &Env) -> Result<Val, Unwind> {
+        let (callee, mut args) = match self {
+            Self::Val(val) => return Ok(val),
+            Self::Call { callee, args } => (callee, args),
+        };
+
+        let _call_guard = env.enter_call()?;
+        let _eval_guard = env.enter_eval()?;
+
+        let mut func = env.get_func(&callee)?;
+        loop {
+            env.consume_fuel()?;
+
+            let def_env = env.find_func_env(&func)?;
+
+            let Func {
+                name,
+                params,
+                body,
+                env_id,
+                args: partial_args,
+            } = func;
+            let mut all_args = if partial_args.items().is_empty() {
+                args
+            } else {
+                let mut all_args = partial_args.items().to_vec();
+                all_args.extend(args);
+                all_args
+            };
+
+            let num_params = params.len();
+            if all_args.len() < num_params {
+                return Ok(Val::Func(Box::new(Func {
+                    name,
+                    params,
+                    body,
+                    env_id,
+                    args: List::new(all_args, env)?,
+                }))
+                .checked()?);
+            }
+            let extra_args = all_args.split_off(num_params);
+
+            let mut call_env = def_env.create_call_frame(env);
+            for (param_name, arg) in params.iter().zip(all_args) {
+                call_env.store_binding(param_name.clone(), arg)?;
+            }
+
+            let result = match body.eval_tail(&mut call_env) {
+                Ok(tail) => tail,
+                Err(Unwind::Return(val)) | Err(Unwind::Propagate(val)) => Self::Val(val),
+                Err(unwind) => return Err(Unwind::Error(unwind.into_error())),
+            };
+
+            if extra_args.is_empty() {
+                match result {
+                    Self::Val(val) => return Ok(val),
+                    // Functions are looked up from where the previous
+                    // function was defined, since that's all a tail call in
+                    // its body could see apart from its own locals.
+                    Self::Call {
+                        callee: next_callee,
+                        args: next_args,
+                    } => {
+                        func = def_env.get_func(&next_callee)?;
+                        args = next_args;
+                    }
+                }
+            } else {
+                // Any arguments left over are passed on to the function that
+                // the call evaluates to.
+                match result.finish(def_env)? {
+                    Val::Func(next_func) => {
+                        func = *next_func;
+                        args = extra_args;
+                    }
+                    _ => {
+                        return Err(Unwind::Error(EvalError::WrongNumberOfParams {
+                            expected: num_params,
+                            got: num_params + extra_args.len(),
+                        }))
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,174 @@
+use crate::env::Env;
+use crate::expr::{Block, Expr, Tail};
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+// An `else if` chain is kept as a list of branches rather than as nested
+// `If`s, so that a long chain doesn't need a deep stack to parse or evaluate.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct If {
+    pub(crate) branches: Vec<(Expr, Block)>,
+    pub(crate) else_branch: Option<Block>,
+}
+
+impl Parsable for If {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::If, |p| {
+            p.expect_keyword("if")?;
+
+            let mut branches = Vec::new();
+            let mut else_branch = None;
+
+            loop {
+                let cond = Expr::parse(p)?;
+                let then_branch = Block::parse(p)?;
+                branches.push((cond, then_branch));
+
+                if !p.eat_keyword("else") {
+                    break;
+                }
+
+                if !p.eat_keyword("if") {
+                    else_branch = Some(Block::parse(p)?);
+                    break;
+                }
+            }
+
+            Ok(Self {
+                branches,
+                else_branch,
+            })
+        })
+    }
+}
+
+impl If {
+    // Finds the block of the first branch whose condition holds.
+    fn taken_branch(&self, env: &Env) -> Result<Option<&Block>, Unwind> {
+        for (cond, then_branch) in &self.branches {
+            if cond.eval(env)?.is_truthy()? {
+                return Ok(Some(then_branch));
+            }
+        }
+
+        Ok(self.else_branch.as_ref())
+    }
+
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        self.taken_branch(env)?
+            .map_or(Ok(Val::Unit), |block| block.eval(env))
+    }
+
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Unwind> {
+        self.taken_branch(env)?
+            .map_or(Ok(Tail::Val(Val::Unit)), |block| block.eval_tail(env))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Number;
+    use crate::stmt::Stmt;
+
+    #[test]
+    fn parse_if_without_else() {
+        assert_eq!(
+            If::new("if 1 { 2 }"),
+            Ok((
+                "",
+                If {
+                    branches: vec![(
+                        Expr::Number(Number(1)),
+                        Block {
+                            stmts: vec![Stmt::Expr(Expr::Number(Number(2)))]
+                        }
+                    )],
+                    else_branch: None,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_if_else_if() {
+        assert_eq!(
+            If::new("if 0 {} else if 1 {} else { 3 }"),
+            Ok((
+                "",
+                If {
+                    branches: vec![
+                        (Expr::Number(Number(0)), Block { stmts: vec![] }),
+                        (Expr::Number(Number(1)), Block { stmts: vec![] }),
+                    ],
+                    else_branch: Some(Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(3)))]
+                    }),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn cannot_parse_if_with_invalid_else_branch() {
+        assert_eq!(
+            If::new("if 1 {} else 2"),
+            Err("expected `{` after `else`".to_owned())
+        );
+    }
+
+    #[test]
+    fn eval_if_with_false_cond_and_no_else() {
+        assert_eq!(
+            If {
+                branches: vec![(
+                    Expr::Number(Number(0)),
+                    Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(2)))]
+                    }
+                )],
+                else_branch: None,
+            }
+            .eval(&Env::default()),
+            Ok(Val::Unit)
+        );
+    }
+
+    #[test]
+    fn eval_if_else() {
+        assert_eq!(
+            If {
+                branches: vec![(
+                    Expr::Number(Number(0)),
+                    Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(2)))]
+                    }
+                )],
+                else_branch: Some(Block {
+                    stmts: vec![Stmt::Expr(Expr::Number(Number(3)))]
+                }),
+            }
+            .eval(&Env::default()),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn eval_first_branch_whose_cond_holds() {
+        let (_, if_else) =
+            If::new("if 0 { 1 } else if 1 { 2 } else if 1 { 3 } else { 4 }").unwrap();
+        assert_eq!(if_else.eval(&Env::default()), Ok(Val::Number(2)));
+    }
+
+    #[test]
+    fn long_else_if_chain_does_not_overflow_stack() {
+        let s = format!("if 0 {{}} {}else {{ 1 }}", "else if 0 {} ".repeat(10_000));
+
+        let parse = crate::parse(&s);
+        assert!(parse.errors().is_empty());
+        assert_eq!(parse.eval(&mut Env::default()), Ok(Val::Number(1)));
+        assert!(crate::format(&s).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::env::Env;
+use crate::expr::Expr;
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::{self, Val};
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct List {
+    pub(crate) items: Vec<Expr>,
+}
+
+impl Parsable for List {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::List, |p| {
+            p.expect_punct("[")?;
+            let mut items = Vec::new();
+
+            while !p.eat_punct("]") {
+                items.push(Expr::parse(p)?);
+
+                if !p.eat_punct(",") {
+                    p.expect_punct("]")?;
+                    break;
+                }
+            }
+
+            Ok(Self { items })
+        })
+    }
+}
+
+impl List {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        env.check_list_len(self.items.len())?;
+
+        let items = self
+            .items
+            .iter()
+            .map(|item| item.eval(env))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Val::List(val::List::new(items, env)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::expr::Number;
+    use crate::limits::Limits;
+
+    #[test]
+    fn parse_empty_list() {
+        assert_eq!(List::new("[ ]"), Ok(("", List { items: vec![] })));
+    }
+
+    #[test]
+    fn parse_list_with_trailing_comma() {
+        assert_eq!(
+            List::new("[1, 2,]"),
+            Ok((
+                "",
+                List {
+                    items: vec![Expr::Number(Number(1)), Expr::Number(Number(2))]
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn cannot_parse_list_without_commas() {
+        assert_eq!(List::new("[1 2]"), Err("expected `]` after `1`".to_owned()));
+    }
+
+    #[test]
+    fn eval_list() {
+        assert_eq!(
+            List {
+                items: vec![Expr::Number(Number(1)), Expr::Number(Number(2))]
+            }
+            .eval(&Env::default()),
+            Ok(Val::List(vec![Val::Number(1), Val::Number(2)].into()))
+        );
+    }
+
+    #[test]
+    fn eval_list_longer_than_limit() {
+        let env = Env::with_limits(Limits {
+            max_list_len: Some(2),
+            ..Limits::default()
+        });
+
+        let (_, list) = List::new("[1, 2, 3]").unwrap();
+        assert_eq!(
+            list.eval(&env),
+            Err(Unwind::Error(EvalError::ListTooLong {
+                len: 3,
+                max_list_len: 2
+            }))
+        );
+    }
+}
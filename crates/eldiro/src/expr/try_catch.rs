@@ -0,0 +1,328 @@
+use crate::env::Env;
+use crate::error::EvalError;
+use crate::expr::Block;
+use crate::lexer::TokenKind;
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct TryCatch {
+    pub(crate) body: Block,
+    pub(crate) catches: Vec<Catch>,
+}
+
+// A `catch` clause. Clauses are tried in order, and an error that none of
+// them matches is passed on.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Catch {
+    pub(crate) pattern: CatchPattern,
+    pub(crate) handler: Block,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum CatchPattern {
+    // `catch e`, which matches any error and binds it as a whole.
+    Any(String),
+    // `catch Overflow lhs rhs`, which matches errors of one kind and binds
+    // either none or all of their fields.
+    Kind { kind: String, fields: Vec<String> },
+}
+
+impl Parsable for TryCatch {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::TryCatch, |p| {
+            p.expect_keyword("try")?;
+            let body = Block::parse(p)?;
+
+            let mut catches = vec![Catch::parse(p)?];
+            while p.at_keyword("catch") {
+                catches.push(Catch::parse(p)?);
+            }
+
+            Ok(Self { body, catches })
+        })
+    }
+}
+
+impl Parsable for Catch {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Catch, |p| {
+            p.expect_keyword("catch")?;
+            let name = p.expect_ident()?.to_owned();
+
+            // Kinds of error are capitalized, which bindings normally aren't.
+            let pattern = if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                let expected = EvalError::catchable_fields(&name)
+                    .ok_or_else(|| format!("cannot catch unknown kind of error `{}`", name))?;
+
+                let mut fields = Vec::new();
+                while p.at_kind(TokenKind::Ident) {
+                    fields.push(p.expect_ident()?.to_owned());
+                }
+
+                if !fields.is_empty() && fields.len() != expected.len() {
+                    return Err(format!(
+                        "expected {} fields for `{}`, got {}",
+                        expected.len(),
+                        name,
+                        fields.len()
+                    ));
+                }
+
+                CatchPattern::Kind { kind: name, fields }
+            } else {
+                CatchPattern::Any(name)
+            };
+
+            let handler = Block::parse(p)?;
+
+            Ok(Self { pattern, handler })
+        })
+    }
+}
+
+impl TryCatch {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        match self.body.eval(env) {
+            Err(Unwind::Error(error)) if error.is_catchable() => {
+                match self.catches.iter().find(|catch| catch.matches(&error)) {
+                    Some(catch) => catch.eval(env, error),
+                    None => Err(Unwind::Error(error)),
+                }
+            }
+            result => result,
+        }
+    }
+}
+
+impl Catch {
+    fn matches(&self, error: &EvalError) -> bool {
+        match &self.pattern {
+            CatchPattern::Any(_) => true,
+            CatchPattern::Kind { kind, .. } => kind == error.kind(),
+        }
+    }
+
+    fn eval(&self, env: &Env, error: EvalError) -> Result<Val, Unwind> {
+        let mut child_env = env.create_child();
+        match &self.pattern {
+            CatchPattern::Any(error_name) => {
+                let error = Val::Error(Box::new(error)).checked()?;
+                child_env.store_binding(error_name.clone(), error)?;
+            }
+            CatchPattern::Kind { fields, .. } => {
+                for (field_name, field) in fields.iter().zip(error.fields()) {
+                    child_env.store_binding(field_name.clone(), field)?;
+                }
+            }
+        }
+
+        self.handler.eval(&child_env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::expr::{BindingUsage, Expr, Number};
+    use crate::limits::Limits;
+    use crate::stmt::Stmt;
+
+    #[test]
+    fn parse_try_catch() {
+        assert_eq!(
+            TryCatch::new("try { 1 } catch e { e }"),
+            Ok((
+                "",
+                TryCatch {
+                    body: Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(1)))]
+                    },
+                    catches: vec![Catch {
+                        pattern: CatchPattern::Any("e".to_owned()),
+                        handler: Block {
+                            stmts: vec![Stmt::Expr(Expr::BindingUsage(BindingUsage {
+                                name: "e".to_owned()
+                            }))]
+                        },
+                    }],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_catches_of_kinds_of_error() {
+        assert_eq!(
+            TryCatch::new("try { 1 } catch Overflow lhs rhs { 2 } catch NonNumberOperands { 3 }"),
+            Ok((
+                "",
+                TryCatch {
+                    body: Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(1)))]
+                    },
+                    catches: vec![
+                        Catch {
+                            pattern: CatchPattern::Kind {
+                                kind: "Overflow".to_owned(),
+                                fields: vec!["lhs".to_owned(), "rhs".to_owned()]
+                            },
+                            handler: Block {
+                                stmts: vec![Stmt::Expr(Expr::Number(Number(2)))]
+                            },
+                        },
+                        Catch {
+                            pattern: CatchPattern::Kind {
+                                kind: "NonNumberOperands".to_owned(),
+                                fields: vec![]
+                            },
+                            handler: Block {
+                                stmts: vec![Stmt::Expr(Expr::Number(Number(3)))]
+                            },
+                        },
+                    ],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn cannot_parse_catch_of_unknown_kind_of_error() {
+        assert_eq!(
+            TryCatch::new("try { 1 } catch DivideByZero { 2 }"),
+            Err("cannot catch unknown kind of error `DivideByZero`".to_owned())
+        );
+    }
+
+    #[test]
+    fn cannot_parse_catch_binding_some_fields() {
+        assert_eq!(
+            TryCatch::new("try { 1 } catch Overflow lhs { 2 }"),
+            Err("expected 2 fields for `Overflow`, got 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn eval_try_catch_without_error() {
+        let (_, try_catch) = TryCatch::new("try { 1 } catch e { 2 }").unwrap();
+        assert_eq!(try_catch.eval(&Env::default()), Ok(Val::Number(1)));
+    }
+
+    #[test]
+    fn eval_try_catch_exposing_error() {
+        let (_, try_catch) = TryCatch::new("try { missing } catch e { Err e }").unwrap();
+        assert_eq!(
+            try_catch.eval(&Env::default()),
+            Ok(Val::Err(Box::new(Val::Error(Box::new(
+                EvalError::BindingDoesNotExist("missing".to_owned())
+            )))))
+        );
+    }
+
+    #[test]
+    fn eval_catch_matching_kind_of_error() {
+        let (_, try_catch) = TryCatch::new(
+            "try { for x in 0..3 { 10 / x } }
+            catch Overflow lhs rhs { 1 }
+            catch DivisionByZero lhs { lhs + 100 }
+            catch e { 3 }",
+        )
+        .unwrap();
+        assert_eq!(try_catch.eval(&Env::default()), Ok(Val::Number(110)));
+
+        let (_, try_catch) =
+            TryCatch::new("try { missing } catch DivisionByZero { 1 } catch e { 2 }").unwrap();
+        assert_eq!(try_catch.eval(&Env::default()), Ok(Val::Number(2)));
+    }
+
+    #[test]
+    fn eval_catch_binding_value_of_error() {
+        let (_, try_catch) =
+            TryCatch::new("try { for x in Ok 5 {} } catch NotIterable val { val }").unwrap();
+        assert_eq!(
+            try_catch.eval(&Env::default()),
+            Ok(Val::Ok(Box::new(Val::Number(5))))
+        );
+    }
+
+    #[test]
+    fn eval_try_catch_passes_on_error_no_catch_matches() {
+        let (_, try_catch) = TryCatch::new("try { missing } catch DivisionByZero { 1 }").unwrap();
+        assert_eq!(
+            try_catch.eval(&Env::default()),
+            Err(Unwind::Error(EvalError::BindingDoesNotExist(
+                "missing".to_owned()
+            )))
+        );
+    }
+
+    #[test]
+    fn eval_try_catch_does_not_catch_exhausted_fuel() {
+        let env = Env::with_limits(Limits {
+            fuel: Some(100),
+            ..Limits::default()
+        });
+
+        let (_, try_catch) = TryCatch::new("try { while 1 {} } catch e { 2 }").unwrap();
+        assert_eq!(
+            try_catch.eval(&env),
+            Err(Unwind::Error(EvalError::FuelExhausted { fuel: 100 }))
+        );
+    }
+
+    #[test]
+    fn eval_try_catch_does_not_catch_exceeded_heap_limit() {
+        let env = Env::with_limits(Limits {
+            max_heap_bytes: Some(10_000),
+            ..Limits::default()
+        });
+
+        let (_, try_catch) = TryCatch::new(
+            "try {
+                let mut x = [0]
+                while 1 { x = [x, x] }
+            } catch e { 2 }",
+        )
+        .unwrap();
+        assert_eq!(
+            try_catch.eval(&env),
+            Err(Unwind::Error(EvalError::HeapLimitExceeded {
+                max_heap_bytes: 10_000
+            }))
+        );
+    }
+
+    #[test]
+    fn caught_error_counts_towards_heap_limit() {
+        let env = Env::with_limits(Limits {
+            max_heap_bytes: Some(100_000),
+            ..Limits::default()
+        });
+
+        let (_, try_catch) = TryCatch::new(
+            "try {
+                let mut x = [0]
+                for i in 0..12 { x = try { [x, x]? } catch e { e } }
+            } catch e { 2 }",
+        )
+        .unwrap();
+        assert_eq!(
+            try_catch.eval(&env),
+            Err(Unwind::Error(EvalError::HeapLimitExceeded {
+                max_heap_bytes: 100_000
+            }))
+        );
+    }
+
+    #[test]
+    fn eval_try_catch_does_not_catch_break() {
+        let (_, try_catch) = TryCatch::new("try { break 1 } catch e { 2 }").unwrap();
+        assert_eq!(
+            try_catch.eval(&Env::default()),
+            Err(Unwind::Break(Val::Number(1)))
+        );
+    }
+}
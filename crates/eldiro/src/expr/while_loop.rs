@@ -0,0 +1,110 @@
+use crate::env::Env;
+use crate::expr::{Block, Expr};
+use crate::parser::{Parsable, Parser};
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct While {
+    pub(crate) cond: Box<Expr>,
+    pub(crate) body: Block,
+}
+
+impl Parsable for While {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::While, |p| {
+            p.expect_keyword("while")?;
+            let cond = Expr::parse(p)?;
+
+            let body = Block::parse(p)?;
+
+            Ok(Self {
+                cond: Box::new(cond),
+                body,
+            })
+        })
+    }
+}
+
+impl While {
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Unwind> {
+        while self.cond.eval(env)?.is_truthy()? {
+            match self.body.eval(env) {
+                Ok(_) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break(val)) => return Ok(val),
+                Err(unwind) => return Err(unwind),
+            }
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::expr::{BindingUsage, Number};
+    use crate::stmt::Stmt;
+
+    #[test]
+    fn parse_while() {
+        assert_eq!(
+            While::new("while x { 1 }"),
+            Ok((
+                "",
+                While {
+                    cond: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "x".to_owned()
+                    })),
+                    body: Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number(1)))]
+                    },
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn eval_while_with_false_cond() {
+        assert_eq!(
+            While {
+                cond: Box::new(Expr::Number(Number(0))),
+                body: Block {
+                    stmts: vec![Stmt::Expr(Expr::Number(Number(1)))]
+                },
+            }
+            .eval(&Env::default()),
+            Ok(Val::Unit)
+        );
+    }
+
+    #[test]
+    fn eval_while_with_break_value() {
+        assert_eq!(
+            While {
+                cond: Box::new(Expr::Number(Number(1))),
+                body: Block {
+                    stmts: vec![Stmt::Expr(Expr::Break(Some(Box::new(Expr::Number(
+                        Number(7)
+                    )))))]
+                },
+            }
+            .eval(&Env::default()),
+            Ok(Val::Number(7))
+        );
+    }
+
+    #[test]
+    fn eval_while_with_non_number_cond() {
+        assert_eq!(
+            While {
+                cond: Box::new(Expr::Block(Block { stmts: vec![] })),
+                body: Block { stmts: vec![] },
+            }
+            .eval(&Env::default()),
+            Err(Unwind::Error(EvalError::NonNumberCondition(Val::Unit)))
+        );
+    }
+}
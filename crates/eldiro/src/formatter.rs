@@ -0,0 +1,551 @@
+use crate::lexer::{self, TokenKind};
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+use std::mem;
+
+const MAX_WIDTH: usize = 80;
+const INDENT: &str = "    ";
+
+// Prints a program without syntax errors in the canonical style. Whitespace
+// is replaced, while comments and single blank lines between statements are
+// kept. Blocks, lists, parentheses and pipe chains are kept on one line if
+// they fit, and are broken over multiple lines otherwise. Calls that don't
+// fit are moved onto a line of their own in parentheses.
+pub(crate) fn format(program: &SyntaxNode) -> String {
+    let mut printer = Printer::default();
+    printer.items(program.children());
+
+    let mut formatted = printer.out.trim_end().to_owned();
+    if !formatted.is_empty() {
+        formatted.push('\n');
+    }
+
+    formatted
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Pending {
+    None,
+    Space,
+    // A line comment was written, so the next token has to go on a new line.
+    Newline,
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+    line_start: bool,
+    pending: Pending,
+    // Set while trying whether a node fits on the rest of the line.
+    flat: bool,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            line_start: false,
+            pending: Pending::None,
+            flat: false,
+        }
+    }
+}
+
+impl Printer {
+    fn write(&mut self, text: &str) {
+        match mem::replace(&mut self.pending, Pending::None) {
+            Pending::Newline => {
+                self.newline();
+                self.out.push_str(&INDENT.repeat(self.indent + 1));
+                self.line_start = false;
+            }
+            Pending::Space if !self.line_start && !self.out.is_empty() => self.out.push(' '),
+            _ => {}
+        }
+
+        if self.line_start {
+            self.out.push_str(&INDENT.repeat(self.indent));
+            self.line_start = false;
+        }
+        self.out.push_str(text);
+    }
+
+    fn space(&mut self) {
+        if self.pending == Pending::None {
+            self.pending = Pending::Space;
+        }
+    }
+
+    fn newline(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+
+        self.out.push('\n');
+        self.line_start = true;
+        self.pending = Pending::None;
+    }
+
+    fn column(&self) -> usize {
+        if self.line_start {
+            return self.indent * INDENT.len();
+        }
+
+        let line_start = self.out.rfind('\n').map_or(0, |idx| idx + 1);
+        self.out[line_start..].chars().count()
+    }
+
+    fn comment(&mut self, comment: &SyntaxToken) {
+        self.space();
+        self.write(comment.text());
+
+        if comment.text().starts_with('#') || comment.text().starts_with("//") {
+            self.pending = Pending::Newline;
+        } else {
+            self.space();
+        }
+    }
+
+    // Prints the statements and comments of a program or block, each on a
+    // line of its own, except for comments at the end of a line.
+    fn items(&mut self, elements: &[SyntaxElement]) {
+        let mut newlines = 0;
+        let mut first = true;
+        // A statement directly after a block comment stays on its line.
+        let mut after_block_comment = false;
+
+        for element in elements {
+            match element {
+                SyntaxElement::Token(token) if token.kind() == TokenKind::Whitespace => {
+                    newlines += count_newlines(token.text());
+                    continue;
+                }
+                SyntaxElement::Token(token) if token.kind() == TokenKind::Comment => {
+                    if newlines > 0 {
+                        self.item_break(first, newlines);
+                    }
+                    self.comment(token);
+                    after_block_comment = self.pending == Pending::Space;
+                }
+                SyntaxElement::Token(_) => continue,
+                SyntaxElement::Node(node) => {
+                    if newlines > 0 || !after_block_comment {
+                        self.item_break(first, newlines);
+                    }
+                    self.node(node);
+                    after_block_comment = false;
+                }
+            }
+
+            newlines = 0;
+            first = false;
+        }
+    }
+
+    fn item_break(&mut self, first: bool, newlines: usize) {
+        if self.out.is_empty() {
+            return;
+        }
+
+        self.newline();
+        if newlines > 1 && !first {
+            self.newline();
+        }
+    }
+
+    fn node(&mut self, node: &SyntaxNode) {
+        match node.kind() {
+            SyntaxKind::Block if is_single_stmt(node) => self.group(node, Self::block),
+            SyntaxKind::Block => self.block(node),
+            SyntaxKind::List => self.group(node, Self::list),
+            SyntaxKind::Paren => self.group(node, Self::paren),
+            SyntaxKind::Pipe => self.group(node, Self::pipe),
+            SyntaxKind::FuncCall => self.group(node, Self::call),
+            _ => self.tokens(node),
+        }
+    }
+
+    // Prints `node` on the rest of the line if it fits, and with `f` as
+    // usual otherwise.
+    fn group(&mut self, node: &SyntaxNode, f: fn(&mut Self, &SyntaxNode)) {
+        if self.flat {
+            f(self, node);
+            return;
+        }
+        self.write("");
+
+        let flat = Self::flat(node, f);
+        if flat.fits(self.column()) {
+            self.out.push_str(&flat.out);
+            self.pending = flat.pending;
+        } else {
+            f(self, node);
+        }
+    }
+
+    // Prints `node` with `f` as if it were all on one line.
+    fn flat(node: &SyntaxNode, f: fn(&mut Self, &SyntaxNode)) -> Self {
+        let mut flat = Self {
+            flat: true,
+            ..Self::default()
+        };
+        f(&mut flat, node);
+
+        flat
+    }
+
+    // Whether what was printed flat fits on a line from `column` on.
+    fn fits(&self, column: usize) -> bool {
+        !self.out.contains('\n')
+            && self.pending != Pending::Newline
+            && column + self.out.chars().count() <= MAX_WIDTH
+    }
+
+    // Prints the tokens of `node` separated by single spaces, except where
+    // there is no space by convention.
+    fn tokens(&mut self, node: &SyntaxNode) {
+        let mut prev = None;
+
+        for child in node.children() {
+            let text = match child {
+                SyntaxElement::Token(token) => match token.kind() {
+                    TokenKind::Whitespace => continue,
+                    TokenKind::Comment => {
+                        self.comment(token);
+                        continue;
+                    }
+                    _ => Some(token.text()),
+                },
+                SyntaxElement::Node(_) => None,
+            };
+
+            if let Some(prev) = prev {
+                if has_space_between(node.kind(), prev, text) {
+                    self.space();
+                }
+            }
+
+            match child {
+                SyntaxElement::Token(token) => self.write(token.text()),
+                SyntaxElement::Node(node) => self.node(node),
+            }
+            prev = Some(text);
+        }
+    }
+
+    fn block(&mut self, node: &SyntaxNode) {
+        if node.child_nodes().next().is_none() && !has_comments(node) {
+            self.write("{}");
+            return;
+        }
+
+        // Blocks with more than one statement or with comments are always
+        // broken, so they never fit.
+        if self.flat && is_single_stmt(node) {
+            self.write("{");
+            for stmt in node.child_nodes() {
+                self.space();
+                self.node(stmt);
+            }
+            self.space();
+            self.write("}");
+            return;
+        }
+
+        self.write("{");
+        self.indent += 1;
+        self.items(node.children());
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    // Broken lists have one item per line, each followed by a comma.
+    fn list(&mut self, node: &SyntaxNode) {
+        let is_broken = !self.flat && node.child_nodes().next().is_some();
+        let mut first = true;
+
+        for child in node.children() {
+            match child {
+                SyntaxElement::Token(token) => match (token.kind(), token.text()) {
+                    (TokenKind::Comment, _) => self.comment(token),
+                    (TokenKind::Punct, "[") => {
+                        self.write("[");
+                        self.indent += usize::from(is_broken);
+                    }
+                    (TokenKind::Punct, "]") if is_broken => {
+                        self.indent -= 1;
+                        self.newline();
+                        self.write("]");
+                    }
+                    (TokenKind::Punct, "]") => self.write("]"),
+                    _ => {}
+                },
+                SyntaxElement::Node(item) if is_broken => {
+                    self.newline();
+                    self.node(item);
+                    self.write(",");
+                }
+                SyntaxElement::Node(item) => {
+                    if !first {
+                        self.write(",");
+                        self.space();
+                    }
+                    self.node(item);
+                    first = false;
+                }
+            }
+        }
+    }
+
+    fn paren(&mut self, node: &SyntaxNode) {
+        if self.flat {
+            self.tokens(node);
+            return;
+        }
+
+        for child in node.children() {
+            match child {
+                SyntaxElement::Token(token) => match (token.kind(), token.text()) {
+                    (TokenKind::Comment, _) => self.comment(token),
+                    (TokenKind::Punct, "(") => {
+                        self.write("(");
+                        self.indent += 1;
+                        self.newline();
+                    }
+                    (TokenKind::Punct, ")") => {
+                        self.indent -= 1;
+                        self.newline();
+                        self.write(")");
+                    }
+                    _ => {}
+                },
+                SyntaxElement::Node(expr) => self.node(expr),
+            }
+        }
+    }
+
+    // The arguments of a call can't be on a line of their own, so a call
+    // that doesn't fit is put in parentheses on a line of its own if it fits
+    // there. Otherwise only its arguments are broken.
+    fn call(&mut self, node: &SyntaxNode) {
+        let column = (self.indent + 1) * INDENT.len();
+        if self.flat || self.column() <= column || !Self::flat(node, Self::tokens).fits(column) {
+            self.tokens(node);
+            return;
+        }
+
+        self.write("(");
+        self.indent += 1;
+        self.newline();
+        self.tokens(node);
+        self.indent -= 1;
+        self.newline();
+        self.write(")");
+    }
+
+    // A broken pipe chain has every `|>` at the start of a line, one level
+    // deeper than the value that is piped. A chain that pipes a bare `return`
+    // or `break` is always broken, since on one line the `|>` would be taken
+    // as the start of its value.
+    fn pipe(&mut self, node: &SyntaxNode) {
+        if self.flat && !pipes_bare_jump(node) {
+            self.tokens(node);
+            return;
+        }
+
+        let mut is_after_arg = false;
+        for child in node.children() {
+            match child {
+                SyntaxElement::Token(token) if token.kind() == TokenKind::Comment => {
+                    self.comment(token)
+                }
+                SyntaxElement::Token(token) if token.text() == "|>" => {
+                    self.newline();
+                    self.write("|>");
+                }
+                SyntaxElement::Token(_) => {}
+                SyntaxElement::Node(arg) if !is_after_arg => {
+                    if arg.kind() == SyntaxKind::Pipe {
+                        self.pipe(arg);
+                    } else {
+                        self.node(arg);
+                    }
+
+                    self.indent += 1;
+                    is_after_arg = true;
+                }
+                // The call a value is piped into can't be in parentheses.
+                SyntaxElement::Node(call) => {
+                    self.space();
+                    self.tokens(call);
+                }
+            }
+        }
+        self.indent -= 1;
+    }
+}
+
+// `prev` and `next` are the texts of tokens, or `None` for nodes.
+fn has_space_between(kind: SyntaxKind, prev: Option<&str>, next: Option<&str>) -> bool {
+    !matches!(
+        (kind, prev, next),
+        (SyntaxKind::Number, _, _)
+            | (SyntaxKind::Unary, _, None)
+            | (SyntaxKind::Propagate, _, Some("?"))
+            | (SyntaxKind::Range, Some(".."), _)
+            | (SyntaxKind::Range, _, Some(".."))
+            | (SyntaxKind::Paren, Some("("), _)
+            | (SyntaxKind::Paren, _, Some(")"))
+            | (SyntaxKind::List, Some("["), _)
+            | (SyntaxKind::List, _, Some("]"))
+            | (SyntaxKind::List, _, Some(","))
+    )
+}
+
+fn pipes_bare_jump(pipe: &SyntaxNode) -> bool {
+    let mut arg = pipe.child_nodes().next();
+    while let Some(node) = arg.filter(|node| node.kind() == SyntaxKind::Pipe) {
+        arg = node.child_nodes().next();
+    }
+
+    arg.is_some_and(|arg| {
+        matches!(
+            arg.kind(),
+            SyntaxKind::Return | SyntaxKind::Break | SyntaxKind::Continue
+        ) && arg.child_nodes().next().is_none()
+    })
+}
+
+fn is_single_stmt(block: &SyntaxNode) -> bool {
+    block.child_nodes().count() == 1 && !has_comments(block)
+}
+
+fn has_comments(node: &SyntaxNode) -> bool {
+    node.child_tokens()
+        .any(|token| token.kind() == TokenKind::Comment)
+}
+
+fn count_newlines(s: &str) -> usize {
+    s.matches(lexer::is_newline).count() - s.matches("\r\n").count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format, Env, Val};
+
+    fn check(s: &str, expected: &str) {
+        assert_eq!(format(s), Ok(expected.to_owned()));
+        assert_eq!(format(expected), Ok(expected.to_owned()));
+    }
+
+    #[test]
+    fn format_spacing() {
+        check(
+            "let mut x=- 1\nx=x+ -2*(3 )\nlet r=0..x+1\nlet l=[1 ,2,]\nf x?|>g",
+            "let mut x = -1\nx = x + -2 * (3)\nlet r = 0..x + 1\nlet l = [1, 2]\nf x? |> g\n",
+        );
+    }
+
+    #[test]
+    fn format_empty_program() {
+        check(" \n\n", "");
+    }
+
+    #[test]
+    fn keep_short_blocks_on_one_line() {
+        check(
+            "fn add a b=>{\n  a+b\n}\nif x {1} else {\n2}\nwhile x{}",
+            "fn add a b => { a + b }\nif x { 1 } else { 2 }\nwhile x {}\n",
+        );
+    }
+
+    #[test]
+    fn indent_block_contents() {
+        check(
+            "fn f x => {\nlet y = x * 2\n  try { g y } catch e {\n  let z = 1\nz\n}\n}",
+            "fn f x => {\n    let y = x * 2\n    try { g y } catch e {\n        let z = 1\n        z\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn keep_comments_and_single_blank_lines() {
+        check(
+            "# header\nlet x = 1 # one\n\n\n\n{ // start\n  /* two */ 2\n  # end\n}\n",
+            "# header\nlet x = 1 # one\n\n{ // start\n    /* two */ 2\n    # end\n}\n",
+        );
+    }
+
+    #[test]
+    fn keep_comments_inside_of_expression() {
+        check(
+            "let x = 1 +   # one\n2 * /* two */ 3",
+            "let x = 1 + # one\n    2 * /* two */ 3\n",
+        );
+    }
+
+    #[test]
+    fn break_long_list() {
+        check(
+            "let xs = [100000000, 200000000, 300000000, 400000000, 500000000, 600000000, 700000000]",
+            "let xs = [\n    100000000,\n    200000000,\n    300000000,\n    400000000,\n    500000000,\n    600000000,\n    700000000,\n]\n",
+        );
+    }
+
+    #[test]
+    fn break_long_call_in_parens() {
+        check(
+            "let y = f (g 100000000 200000000) (h 100000000 200000000 300000000 400000000 500000000)",
+            "let y = f (g 100000000 200000000) (\n    h 100000000 200000000 300000000 400000000 500000000\n)\n",
+        );
+    }
+
+    #[test]
+    fn wrap_long_call_in_parens() {
+        check(
+            "let total = add_all 100000000 200000000 300000000 400000000 500000000 600000000 700",
+            "let total = (\n    add_all 100000000 200000000 300000000 400000000 500000000 600000000 700\n)\n",
+        );
+        check(
+            "fn f => { 1000000 + add_all 100000000 200000000 300000000 400000000 500000000 600000000 700 }",
+            "fn f => {\n    1000000 + (\n        add_all 100000000 200000000 300000000 400000000 500000000 600000000 700\n    )\n}\n",
+        );
+    }
+
+    #[test]
+    fn break_long_pipe_chain() {
+        check(
+            "xs |> map double |> filter is_positive |> map increment |> fold add 0 |> print_all",
+            "xs\n    |> map double\n    |> filter is_positive\n    |> map increment\n    |> fold add 0\n    |> print_all\n",
+        );
+    }
+
+    #[test]
+    fn keep_pipe_after_bare_jump_on_next_line() {
+        check("return\n|> continuebreak", "return\n    |> continuebreak\n");
+        check(
+            "fn f x => { return\n|> continuebreak }\nwhile 1 { [break\n|> g |> h] }",
+            "fn f x => {\n    return\n        |> continuebreak\n}\nwhile 1 {\n    [\n        break\n            |> g\n            |> h,\n    ]\n}\n",
+        );
+    }
+
+    #[test]
+    fn formatted_program_evaluates_the_same() {
+        let s = "fn double n=>n*2 # twice\nfn sum xs => {\n let mut total=0\nfor x in xs {total=total+x}\n total }\nsum [1 ,-2, 3 |> double,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24]";
+        let formatted = format(s).unwrap();
+
+        assert_eq!(
+            crate::parse(&formatted).eval(&mut Env::default()),
+            Ok(Val::Number(299))
+        );
+        assert_eq!(
+            crate::parse(s).eval(&mut Env::default()),
+            Ok(Val::Number(299))
+        );
+    }
+
+    #[test]
+    fn cannot_format_program_with_errors() {
+        let errors = format("let x =").unwrap_err();
+        assert_eq!(errors[0].message(), "expected expression after `=`");
+    }
+}
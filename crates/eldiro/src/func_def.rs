@@ -1,6 +1,9 @@
 use crate::env::Env;
+use crate::error::EvalError;
+use crate::lexer::TokenKind;
+use crate::parser::{Parsable, Parser};
 use crate::stmt::Stmt;
-use crate::utils;
+use crate::syntax::SyntaxKind;
 
 #[cfg(test)]
 mod tests {
@@ -37,6 +40,14 @@ mod tests {
         )
     }
 
+    #[test]
+    fn cannot_parse_func_def_named_after_keyword() {
+        assert_eq!(
+            FuncDef::new("fn fn => 1"),
+            Err("keyword 'fn' cannot be used as an identifier".to_owned())
+        );
+    }
+
     #[test]
     fn parse_func_def_with_multiple_params() {
         assert_eq!(
@@ -67,37 +78,31 @@ pub(crate) struct FuncDef {
     pub(crate) body: Box<Stmt>,
 }
 
-impl FuncDef {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let s = utils::tag("fn", s)?;
-        let (s, _) = utils::extract_whitespace1(s)?;
-
-        let (s, name) = utils::extract_ident(s)?;
-        let (s, _) = utils::extract_whitespace(s);
-
-        let (s, params) = utils::sequence(
-            |s| utils::extract_ident(s).map(|(s, ident)| (s, ident.to_owned())),
-            utils::extract_whitespace,
-            s,
-        )?;
+impl Parsable for FuncDef {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::FuncDef, |p| {
+            p.expect_keyword("fn")?;
+            let name = p.expect_ident()?.to_owned();
 
-        let s = utils::tag("=>", s)?;
-        let (s, _) = utils::extract_whitespace(s);
+            let mut params = Vec::new();
+            while p.at_kind(TokenKind::Ident) {
+                params.push(p.bump().text.to_owned());
+            }
 
-        let (s, body) = Stmt::new(s)?;
+            p.expect_punct("=>")?;
+            let body = Stmt::parse(p)?;
 
-        Ok((
-            s,
-            FuncDef {
-                name: name.to_owned(),
+            Ok(Self {
+                name,
                 params,
                 body: Box::new(body),
-            },
-        ))
+            })
+        })
     }
+}
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<(), String> {
-        env.store_func(self.name.clone(), self.params.clone(), *self.body.clone());
-        Ok(())
+impl FuncDef {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<(), EvalError> {
+        env.store_func(self.name.clone(), self.params.clone(), *self.body.clone())
     }
 }
@@ -0,0 +1,339 @@
+use crate::utils;
+use std::ops::Range;
+use unicode_xid::UnicodeXID;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    Ident,
+    Keyword,
+    Number,
+    Punct,
+    Error,
+}
+
+impl TokenKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: &'a str,
+    pub(crate) span: Range<usize>,
+}
+
+impl Token<'_> {
+    pub(crate) fn is(&self, kind: TokenKind, text: &str) -> bool {
+        self.kind == kind && self.text == text
+    }
+
+    pub(crate) fn error_message(&self) -> String {
+        if self.text.starts_with("/*") {
+            "unterminated block comment".to_owned()
+        } else {
+            format!("unexpected character '{}'", self.text)
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "break", "catch", "continue", "else", "Err", "fn", "for", "if", "in", "let", "mut", "Ok",
+    "return", "try", "while",
+];
+
+// Longer punctuation comes before its prefixes, so that `|>` isn't lexed as
+// `|` followed by `>`.
+const PUNCTUATION: &[&str] = &[
+    "|>", "=>", "..", "**", "<<", ">>", "+", "-", "*", "/", "%", "&", "|", "^", "~", "!", "=", "?",
+    "(", ")", "{", "}", "[", "]", ",",
+];
+
+pub(crate) fn is_newline(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{0B}' | '\u{0C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+// Splits the whole input into tokens, including whitespace and comments, so
+// that the tokens add up to the input. Input that can't be lexed ends up in
+// error tokens, which the parser reports once it gets to them.
+pub(crate) fn lex(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+
+    while offset < s.len() {
+        let remainder = &s[offset..];
+        let (kind, len) = lex_token(remainder);
+
+        tokens.push(Token {
+            kind,
+            text: &remainder[..len],
+            span: offset..offset + len,
+        });
+        offset += len;
+    }
+
+    tokens
+}
+
+fn lex_token(s: &str) -> (TokenKind, usize) {
+    let first = s.chars().next().expect("input is not empty");
+
+    let (kind, remainder) = if first.is_whitespace() {
+        (
+            TokenKind::Whitespace,
+            utils::take_while(char::is_whitespace, s).0,
+        )
+    } else if s.starts_with('#') || s.starts_with("//") {
+        // Line comments start with `#` or `//` and run until the end of the
+        // line.
+        (
+            TokenKind::Comment,
+            utils::take_while(|c| !is_newline(c), s).0,
+        )
+    } else if s.starts_with("/*") {
+        match skip_block_comment(s) {
+            Some(remainder) => (TokenKind::Comment, remainder),
+            None => (TokenKind::Error, ""),
+        }
+    } else if first == '_' || first.is_xid_start() {
+        let (remainder, word) = utils::take_while(UnicodeXID::is_xid_continue, s);
+
+        if KEYWORDS.contains(&word) {
+            (TokenKind::Keyword, remainder)
+        } else {
+            (TokenKind::Ident, remainder)
+        }
+    } else if first.is_ascii_digit() {
+        // Letters are taken as well, so that `0xff` is a single token, and
+        // `12ab` is reported as a malformed literal rather than parsed as a
+        // number followed by an identifier.
+        (
+            TokenKind::Number,
+            utils::take_while(UnicodeXID::is_xid_continue, s).0,
+        )
+    } else if let Some(punct) = PUNCTUATION.iter().find(|punct| s.starts_with(*punct)) {
+        (TokenKind::Punct, &s[punct.len()..])
+    } else {
+        (TokenKind::Error, &s[first.len_utf8()..])
+    };
+
+    (kind, s.len() - remainder.len())
+}
+
+// Block comments are delimited by `/*` and `*/` and can be nested.
+fn skip_block_comment(s: &str) -> Option<&str> {
+    let mut s = &s[2..];
+    let mut depth = 1;
+
+    while depth > 0 {
+        if let Some(new_s) = s.strip_prefix("/*") {
+            depth += 1;
+            s = new_s;
+        } else if let Some(new_s) = s.strip_prefix("*/") {
+            depth -= 1;
+            s = new_s;
+        } else {
+            let mut chars = s.chars();
+            chars.next()?;
+            s = chars.as_str();
+        }
+    }
+
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_and_texts(s: &str) -> Vec<(TokenKind, &str)> {
+        lex(s)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn lex_empty_input() {
+        assert_eq!(lex(""), vec![]);
+    }
+
+    #[test]
+    fn lex_tokens_with_spans() {
+        assert_eq!(
+            lex("let x"),
+            vec![
+                Token {
+                    kind: TokenKind::Keyword,
+                    text: "let",
+                    span: 0..3,
+                },
+                Token {
+                    kind: TokenKind::Whitespace,
+                    text: " ",
+                    span: 3..4,
+                },
+                Token {
+                    kind: TokenKind::Ident,
+                    text: "x",
+                    span: 4..5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_numbers_and_ops() {
+        assert_eq!(
+            kinds_and_texts("11+2"),
+            vec![
+                (TokenKind::Number, "11"),
+                (TokenKind::Punct, "+"),
+                (TokenKind::Number, "2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_number_with_letters() {
+        assert_eq!(
+            kinds_and_texts("0x1_f..12ab"),
+            vec![
+                (TokenKind::Number, "0x1_f"),
+                (TokenKind::Punct, ".."),
+                (TokenKind::Number, "12ab"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_longest_punctuation() {
+        assert_eq!(
+            kinds_and_texts("**|>|=>="),
+            vec![
+                (TokenKind::Punct, "**"),
+                (TokenKind::Punct, "|>"),
+                (TokenKind::Punct, "|"),
+                (TokenKind::Punct, "=>"),
+                (TokenKind::Punct, "="),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_idents() {
+        assert_eq!(
+            kinds_and_texts("my_value _ größe 変数 a1"),
+            vec![
+                (TokenKind::Ident, "my_value"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "_"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "größe"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "変数"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "a1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_keywords_only_as_whole_words() {
+        assert_eq!(
+            kinds_and_texts("let letter let_x fnä breakfast"),
+            vec![
+                (TokenKind::Keyword, "let"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "letter"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "let_x"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "fnä"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "breakfast"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_line_comments() {
+        assert_eq!(
+            kinds_and_texts(" # one\n// two\n3"),
+            vec![
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Comment, "# one"),
+                (TokenKind::Whitespace, "\n"),
+                (TokenKind::Comment, "// two"),
+                (TokenKind::Whitespace, "\n"),
+                (TokenKind::Number, "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_line_comment_ending_in_crlf() {
+        assert_eq!(
+            kinds_and_texts("# c\r\n3"),
+            vec![
+                (TokenKind::Comment, "# c"),
+                (TokenKind::Whitespace, "\r\n"),
+                (TokenKind::Number, "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_nested_block_comment() {
+        assert_eq!(
+            kinds_and_texts("/* a /* b */ c */3"),
+            vec![
+                (TokenKind::Comment, "/* a /* b */ c */"),
+                (TokenKind::Number, "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_unterminated_block_comment() {
+        let tokens = lex("1 /* a /* b */ 3");
+
+        assert_eq!(tokens[2].kind, TokenKind::Error);
+        assert_eq!(tokens[2].text, "/* a /* b */ 3");
+        assert_eq!(tokens[2].error_message(), "unterminated block comment");
+    }
+
+    #[test]
+    fn lex_tabs_crlf_and_unicode_whitespace() {
+        assert_eq!(
+            kinds_and_texts("\t \r\n\u{A0}\u{3000}\u{2028}3"),
+            vec![
+                (TokenKind::Whitespace, "\t \r\n\u{A0}\u{3000}\u{2028}"),
+                (TokenKind::Number, "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_unknown_character() {
+        let tokens = lex("€x");
+
+        assert_eq!(tokens[0].kind, TokenKind::Error);
+        assert_eq!(tokens[0].error_message(), "unexpected character '€'");
+        assert_eq!(tokens[1].kind, TokenKind::Ident);
+    }
+
+    #[test]
+    fn tokens_add_up_to_input() {
+        let s = "fn f x => { x + 1 } # done\n/* c */ f 0x1_0 |> g €";
+        let text: String = lex(s).into_iter().map(|token| token.text).collect();
+
+        assert_eq!(text, s);
+    }
+}
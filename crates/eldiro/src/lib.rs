@@ -1,27 +1,279 @@
+pub mod ast;
 mod binding_def;
 mod env;
+mod error;
 mod expr;
+mod formatter;
 mod func_def;
+mod lexer;
+mod limits;
+mod parser;
+mod program;
 mod stmt;
+mod syntax;
+mod unwind;
 mod utils;
 mod val;
 
 pub use env::Env;
-pub use val::Val;
+pub use error::EvalError;
+pub use expr::Op;
+pub use lexer::TokenKind;
+pub use limits::Limits;
+pub use syntax::{SyntaxElement, SyntaxError, SyntaxKind, SyntaxNode, SyntaxToken};
+pub use val::{Func, List, Val};
 
+use parser::{Parsable, Parser};
+use std::fmt;
+
+/// The result of parsing a program. Parsing always produces a syntax tree
+/// that adds up to the input, even when the input has errors. Statements with
+/// errors are skipped, so the tree of the rest of the program is still usable.
 #[derive(Debug)]
-pub struct Parse(stmt::Stmt);
+pub struct Parse {
+    program: program::Program,
+    syntax: SyntaxNode,
+    errors: Vec<SyntaxError>,
+}
+
+pub fn parse(s: &str) -> Parse {
+    let mut p = Parser::new(s);
+    let program = program::Program::parse(&mut p).unwrap_or_else(|message| {
+        p.error(message);
+        program::Program::default()
+    });
+    let (syntax, errors) = p.finish();
 
-pub fn parse(s: &str) -> Result<Parse, String> {
-    let (s, stmt) = stmt::Stmt::new(s)?;
-    match s.len() {
-        0 => Ok(Parse(stmt)),
-        _ => Err("input was not consumed fully by parser.".to_owned()),
+    Parse {
+        program,
+        syntax,
+        errors,
+    }
+}
+
+/// Formats a program in the canonical style, keeping its comments. Programs
+/// with syntax errors aren't formatted.
+///
+/// Lines are kept to 80 columns where possible by breaking long blocks,
+/// lists, parenthesized expressions and pipe chains over multiple lines. The
+/// arguments of a call have to be on the same line as the function, so a long
+/// call is put in parentheses on a line of its own instead, or if it doesn't
+/// fit there either, its parenthesized arguments are broken.
+pub fn format(s: &str) -> Result<String, Vec<SyntaxError>> {
+    let parse = parse(s);
+
+    match parse.errors.len() {
+        0 => Ok(formatter::format(&parse.syntax)),
+        _ => Err(parse.errors),
     }
 }
 
 impl Parse {
-    pub fn eval(&self, env: &mut Env) -> Result<Val, String> {
-        self.0.eval(env)
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.syntax
+    }
+
+    pub fn ast(&self) -> ast::Program<'_> {
+        ast::Program::cast(&self.syntax).expect("the root is a program")
+    }
+
+    /// Evaluates the program, failing without evaluating anything if it has
+    /// syntax errors, since the statements that were skipped could change
+    /// what the rest of it does.
+    pub fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        if !self.errors.is_empty() {
+            return Err(EvalError::SyntaxErrors(self.errors.clone()));
+        }
+
+        self.program.eval(env).map_err(unwind::Unwind::into_error)
+    }
+}
+
+impl fmt::Display for Parse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.syntax)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_preserves_whitespace_and_comments() {
+        let s = "# add\nfn add a b =>\ta + b /* sum */\r\n\nadd 1 2 // 3\n";
+        let parse = parse(s);
+
+        assert_eq!(parse.errors(), &[]);
+        assert_eq!(parse.to_string(), s);
+        assert_eq!(parse.syntax().span(), 0..s.len());
+    }
+
+    fn error_messages(s: &str) -> Vec<String> {
+        parse(s)
+            .errors()
+            .iter()
+            .map(|error| error.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_input_with_error() {
+        let s = "let x = 1\nlet = 2 } €\n";
+        let parse = parse(s);
+
+        assert_eq!(
+            parse.errors(),
+            &[
+                SyntaxError::new("expected identifier after `let`".to_owned(), 14..15),
+                SyntaxError::new("unexpected character '€'".to_owned(), 20..23),
+            ]
+        );
+        assert_eq!(parse.to_string(), s);
+        assert_eq!(
+            parse.eval(&mut Env::default()),
+            Err(EvalError::SyntaxErrors(parse.errors().to_vec()))
+        );
+
+        let names: Vec<_> = parse
+            .ast()
+            .stmts()
+            .map(|stmt| match stmt {
+                ast::Stmt::BindingDef(binding_def) => binding_def.name(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec![Some("x"), None]);
+    }
+
+    #[test]
+    fn recover_at_start_of_next_line() {
+        assert_eq!(
+            error_messages("let x =\nlet y = (1\nlet z = 1 + ]\nz"),
+            vec![
+                "expected expression after `=`",
+                "expected `)` after `1`",
+                "expected expression after `+`",
+            ]
+        );
+    }
+
+    #[test]
+    fn recover_inside_of_block() {
+        let s = "fn f x => {\n  let y = )\n  x\n}\nlet z = ]\nf 1";
+        assert_eq!(
+            error_messages(s),
+            vec![
+                "expected expression after `=`",
+                "expected expression after `=`"
+            ]
+        );
+
+        let parse = parse(s);
+        let body = match parse.ast().stmts().next() {
+            Some(ast::Stmt::FuncDef(func_def)) => func_def.body(),
+            _ => None,
+        };
+        match body {
+            Some(ast::Stmt::Expr(ast::Expr::Block(block))) => assert_eq!(block.stmts().count(), 2),
+            body => panic!("unexpected body {:?}", body),
+        }
+    }
+
+    #[test]
+    fn skip_over_braces_when_recovering() {
+        assert_eq!(
+            error_messages("let x = ) {\n  let\n}\n1 }\n2"),
+            vec!["expected expression after `=`", "expected expression"]
+        );
+    }
+
+    #[test]
+    fn parse_and_eval_random_input_without_panicking() {
+        const FRAGMENTS: &[&str] = &[
+            "let",
+            "mut",
+            "fn",
+            "=>",
+            "=",
+            "if",
+            "else",
+            "else if",
+            "while",
+            "for",
+            "in",
+            "break",
+            "continue",
+            "return",
+            "try",
+            "catch",
+            "DivisionByZero",
+            "Ok",
+            "Err",
+            "{",
+            "}",
+            "[",
+            "(",
+            ")",
+            "]",
+            ",",
+            "..",
+            "|>",
+            ".",
+            "?",
+            "+",
+            "-",
+            "*",
+            "/",
+            "!",
+            "%",
+            "**",
+            "&",
+            "|",
+            "^",
+            "~",
+            "<<",
+            ">>",
+            "0x",
+            "0b1",
+            "_",
+            "0",
+            "1",
+            "2147483647",
+            "99999999999",
+            "x",
+            "é",
+            "🦀",
+            " ",
+            "\n",
+            "\t",
+            "_",
+        ];
+
+        let mut next = crate::utils::xorshift(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..20_000 {
+            let len = next(40);
+            let input: String = (0..len).map(|_| FRAGMENTS[next(FRAGMENTS.len())]).collect();
+
+            // Evaluation needs a budget, since random input may loop forever.
+            let parse = parse(&input);
+            assert_eq!(parse.to_string(), input);
+
+            if let Ok(formatted) = format(&input) {
+                assert_eq!(format(&formatted), Ok(formatted.clone()), "{:?}", input);
+            }
+
+            if parse.errors().is_empty() {
+                let _ = parse.eval(&mut Env::with_limits(Limits {
+                    fuel: Some(10_000),
+                    ..Limits::default()
+                }));
+            }
+        }
     }
 }
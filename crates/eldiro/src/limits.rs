@@ -0,0 +1,268 @@
+use crate::error::EvalError;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Caps on the resources a script may use. Eldiro has no strings, so there is
+/// no limit on their length.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Limits {
+    /// How many function calls may be in progress at once.
+    pub max_call_depth: usize,
+    /// How many bytes of native stack evaluation may use. Evaluation recurses
+    /// for every nested expression and call, so how much stack a script
+    /// takes depends on how its code is nested as well as on how deeply it
+    /// calls functions. The default leaves room to spare in the 2 MiB stack
+    /// that spawned threads get, so raising it may require running
+    /// evaluation on a thread with a larger stack.
+    pub max_stack_bytes: usize,
+    /// How many evaluation steps may be taken in total, or `None` for no
+    /// limit. Every evaluated expression and function call is one step.
+    pub fuel: Option<u64>,
+    /// The longest list that may be created, or `None` for no limit. List
+    /// literals are the only way to create a list, so this is checked when
+    /// one is evaluated.
+    pub max_list_len: Option<usize>,
+    /// How many bindings and functions may be live at once across all
+    /// scopes, or `None` for no limit.
+    pub max_bindings: Option<usize>,
+    /// Roughly how many bytes live values may take up, or `None` for no
+    /// limit. A list is counted from when it's created until the last copy
+    /// of it is dropped, along with the full size of any lists it holds, and
+    /// everything else is counted while a binding holds it.
+    pub max_heap_bytes: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_call_depth: 128,
+            max_stack_bytes: 1536 * 1024,
+            fuel: None,
+            max_list_len: None,
+            max_bindings: None,
+            max_heap_bytes: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub(crate) struct Context {
+    limits: Limits,
+    call_depth: Cell<usize>,
+    eval_depth: Cell<usize>,
+    stack_base: Cell<usize>,
+    fuel_consumed: Cell<u64>,
+    live_bindings: Cell<usize>,
+    heap_bytes: Cell<usize>,
+    envs_created: Cell<usize>,
+}
+
+impl Context {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            call_depth: Cell::new(0),
+            eval_depth: Cell::new(0),
+            stack_base: Cell::new(0),
+            fuel_consumed: Cell::new(0),
+            live_bindings: Cell::new(0),
+            heap_bytes: Cell::new(0),
+            envs_created: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn consume_fuel(&self) -> Result<(), EvalError> {
+        let fuel_consumed = self.fuel_consumed.get();
+        if let Some(fuel) = self.limits.fuel {
+            if fuel_consumed >= fuel {
+                return Err(EvalError::FuelExhausted { fuel });
+            }
+        }
+
+        self.fuel_consumed.set(fuel_consumed + 1);
+        Ok(())
+    }
+
+    pub(crate) fn fuel_consumed(&self) -> u64 {
+        self.fuel_consumed.get()
+    }
+
+    pub(crate) fn check_list_len(&self, len: usize) -> Result<(), EvalError> {
+        match self.limits.max_list_len {
+            Some(max_list_len) if len > max_list_len => {
+                Err(EvalError::ListTooLong { len, max_list_len })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn allocate_binding(&self, bytes: usize) -> Result<(), EvalError> {
+        let live_bindings = self.live_bindings.get();
+        if let Some(max_bindings) = self.limits.max_bindings {
+            if live_bindings >= max_bindings {
+                return Err(EvalError::TooManyBindings { max_bindings });
+            }
+        }
+
+        self.reallocate(0, bytes)?;
+        self.live_bindings.set(live_bindings + 1);
+        Ok(())
+    }
+
+    pub(crate) fn reallocate(&self, old_bytes: usize, new_bytes: usize) -> Result<(), EvalError> {
+        let heap_bytes = self.heap_bytes.get() - old_bytes + new_bytes;
+        if let Some(max_heap_bytes) = self.limits.max_heap_bytes {
+            if heap_bytes > max_heap_bytes {
+                return Err(EvalError::HeapLimitExceeded { max_heap_bytes });
+            }
+        }
+
+        self.heap_bytes.set(heap_bytes);
+        Ok(())
+    }
+
+    pub(crate) fn release_binding(&self, bytes: usize) {
+        self.live_bindings.set(self.live_bindings.get() - 1);
+        self.heap_bytes.set(self.heap_bytes.get() - bytes);
+    }
+
+    // Environments are numbered from 1, since the root one is always 0.
+    pub(crate) fn new_env_id(&self) -> usize {
+        let id = self.envs_created.get() + 1;
+        self.envs_created.set(id);
+        id
+    }
+
+    pub(crate) fn enter_call(&self) -> Result<DepthGuard<'_>, EvalError> {
+        if self.call_depth.get() >= self.limits.max_call_depth {
+            return Err(EvalError::StackOverflow {
+                max_call_depth: self.limits.max_call_depth,
+            });
+        }
+
+        Ok(DepthGuard::new(&self.call_depth))
+    }
+
+    // Stack use is measured from where the outermost evaluation started, so
+    // that whatever the embedder has on the stack already doesn't count.
+    pub(crate) fn enter_eval(&self) -> Result<DepthGuard<'_>, EvalError> {
+        let stack_address = stack_address();
+        if self.eval_depth.get() == 0 {
+            self.stack_base.set(stack_address);
+        } else if self.stack_base.get().abs_diff(stack_address) > self.limits.max_stack_bytes {
+            return Err(EvalError::StackExhausted {
+                max_stack_bytes: self.limits.max_stack_bytes,
+            });
+        }
+
+        Ok(DepthGuard::new(&self.eval_depth))
+    }
+}
+
+// Bytes that count towards the heap limit for as long as this is around.
+pub(crate) struct HeapCharge {
+    context: Rc<Context>,
+    bytes: usize,
+}
+
+impl HeapCharge {
+    pub(crate) fn new(context: &Rc<Context>, bytes: usize) -> Result<Self, EvalError> {
+        context.reallocate(0, bytes)?;
+
+        Ok(Self {
+            context: Rc::clone(context),
+            bytes,
+        })
+    }
+}
+
+impl Drop for HeapCharge {
+    fn drop(&mut self) {
+        let heap_bytes = &self.context.heap_bytes;
+        heap_bytes.set(heap_bytes.get() - self.bytes);
+    }
+}
+
+// The address of a local variable, which tells how far down the native stack
+// the caller is.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    &marker as *const u8 as usize
+}
+
+// Counts one level of depth for as long as it's alive.
+pub(crate) struct DepthGuard<'a>(&'a Cell<usize>);
+
+impl<'a> DepthGuard<'a> {
+    fn new(depth: &'a Cell<usize>) -> Self {
+        depth.set(depth.get() + 1);
+        Self(depth)
+    }
+}
+
+impl Drop for DepthGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaving_call_frees_up_depth() {
+        let context = Context::new(Limits {
+            max_call_depth: 1,
+            ..Limits::default()
+        });
+
+        let guard = context.enter_call();
+        assert!(guard.is_ok());
+        assert_eq!(
+            context.enter_call().map(|_| ()),
+            Err(EvalError::StackOverflow { max_call_depth: 1 })
+        );
+
+        drop(guard);
+        assert!(context.enter_call().is_ok());
+    }
+
+    #[test]
+    fn consume_fuel_until_exhausted() {
+        let context = Context::new(Limits {
+            fuel: Some(2),
+            ..Limits::default()
+        });
+
+        assert_eq!(context.consume_fuel(), Ok(()));
+        assert_eq!(context.consume_fuel(), Ok(()));
+        assert_eq!(
+            context.consume_fuel(),
+            Err(EvalError::FuelExhausted { fuel: 2 })
+        );
+        assert_eq!(context.fuel_consumed(), 2);
+    }
+
+    #[test]
+    fn releasing_binding_frees_up_space() {
+        let context = Context::new(Limits {
+            max_bindings: Some(1),
+            max_heap_bytes: Some(10),
+            ..Limits::default()
+        });
+
+        assert_eq!(context.allocate_binding(8), Ok(()));
+        assert_eq!(
+            context.allocate_binding(0),
+            Err(EvalError::TooManyBindings { max_bindings: 1 })
+        );
+        assert_eq!(
+            context.reallocate(8, 11),
+            Err(EvalError::HeapLimitExceeded { max_heap_bytes: 10 })
+        );
+
+        context.release_binding(8);
+        assert_eq!(context.allocate_binding(10), Ok(()));
+    }
+}
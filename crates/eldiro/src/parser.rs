@@ -0,0 +1,476 @@
+use crate::lexer::{self, Token, TokenKind};
+use crate::syntax::{SyntaxElement, SyntaxError, SyntaxKind, SyntaxNode, SyntaxToken};
+use std::mem;
+
+pub(crate) trait Parsable: Sized {
+    fn parse(p: &mut Parser) -> Result<Self, String>;
+
+    // Parses the start of `s`, returning the input after the last token that
+    // was used. Fails on the first syntax error, even if the parser recovered
+    // from it.
+    fn new(s: &str) -> Result<(&str, Self), String> {
+        let mut p = Parser::new(s);
+        let parsed = Self::parse(&mut p)?;
+
+        match p.errors.first() {
+            Some(error) => Err(error.message().to_owned()),
+            None => Ok((p.remainder(), parsed)),
+        }
+    }
+}
+
+// Newlines are insignificant, except that they end the arguments of a
+// function call, the value of a `break` or `return`, and the left operand of a
+// binary operator or range. This way a statement can be followed by one that
+// starts with `-` or `(` on the next line.
+//
+// While parsing, the parser also builds a lossless syntax tree. Tokens are
+// added to the innermost open node as they are bumped, together with the
+// whitespace and comments before them. Whitespace and comments before the
+// start of a node go to its parent instead.
+pub(crate) struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    offset: usize,
+    children: Vec<SyntaxElement>,
+    open_nodes: Vec<(SyntaxKind, usize)>,
+    errors: Vec<SyntaxError>,
+    stmt_start: usize,
+}
+
+// The position in the syntax tree before a node whose kind isn't known until
+// after its first child has been parsed, like the left operand of an
+// operation.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Checkpoint(usize);
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            tokens: lexer::lex(source),
+            pos: 0,
+            offset: 0,
+            children: Vec::new(),
+            open_nodes: Vec::new(),
+            errors: Vec::new(),
+            stmt_start: 0,
+        }
+    }
+
+    fn nth_index(&self, n: usize) -> Option<usize> {
+        (self.pos..self.tokens.len())
+            .filter(|&idx| !self.tokens[idx].kind.is_trivia())
+            .nth(n)
+    }
+
+    // The `n`th token after the current position, skipping over whitespace
+    // and comments.
+    pub(crate) fn nth(&self, n: usize) -> Option<&Token<'a>> {
+        self.nth_index(n).map(|idx| &self.tokens[idx])
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token<'a>> {
+        self.nth(0)
+    }
+
+    pub(crate) fn at(&self, kind: TokenKind, text: &str) -> bool {
+        self.peek().is_some_and(|token| token.is(kind, text))
+    }
+
+    pub(crate) fn at_punct(&self, punct: &str) -> bool {
+        self.at(TokenKind::Punct, punct)
+    }
+
+    pub(crate) fn at_keyword(&self, keyword: &str) -> bool {
+        self.at(TokenKind::Keyword, keyword)
+    }
+
+    pub(crate) fn at_kind(&self, kind: TokenKind) -> bool {
+        self.peek().is_some_and(|token| token.kind == kind)
+    }
+
+    pub(crate) fn at_end(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    // Whether the whitespace before the `n`th token contains a newline. The
+    // end of the input counts as a newline, while newlines inside of block
+    // comments don't count.
+    pub(crate) fn newline_before(&self, n: usize) -> bool {
+        let end = match self.nth_index(n) {
+            Some(idx) => idx,
+            None => return true,
+        };
+        // Whitespace before the next token may already be in the tree, so
+        // the search starts right after the last token that was bumped.
+        let start = match n {
+            0 => self.tokens[..self.pos]
+                .iter()
+                .rposition(|token| !token.kind.is_trivia())
+                .map_or(0, |idx| idx + 1),
+            _ => self.nth_index(n - 1).expect("earlier token exists") + 1,
+        };
+
+        self.tokens[start..end].iter().any(|token| {
+            token.kind == TokenKind::Whitespace && token.text.contains(lexer::is_newline)
+        })
+    }
+
+    // Whether the next token directly follows the last one, without any
+    // whitespace or comments in between.
+    pub(crate) fn is_adjacent(&self) -> bool {
+        self.peek()
+            .is_some_and(|token| token.span.start == self.offset)
+    }
+
+    pub(crate) fn bump(&mut self) -> Token<'a> {
+        let idx = self.nth_index(0).expect("there is a token to bump");
+        let token = self.tokens[idx].clone();
+
+        self.push_tokens(idx + 1);
+        self.offset = token.span.end;
+
+        token
+    }
+
+    // Adds the tokens up to `end` to the innermost open node.
+    fn push_tokens(&mut self, end: usize) {
+        for token in &self.tokens[self.pos..end] {
+            self.children.push(SyntaxElement::Token(SyntaxToken::new(
+                token.kind,
+                token.text,
+                token.span.clone(),
+            )));
+        }
+
+        self.pos = end;
+    }
+
+    fn push_trivia(&mut self) {
+        let end = self.nth_index(0).unwrap_or(self.tokens.len());
+        self.push_tokens(end);
+    }
+
+    pub(crate) fn start_node(&mut self, kind: SyntaxKind) {
+        // Whitespace and comments before the root node are part of it, so
+        // that the root covers the whole input.
+        if !self.open_nodes.is_empty() {
+            self.push_trivia();
+        }
+
+        self.open_nodes.push((kind, self.children.len()));
+    }
+
+    pub(crate) fn checkpoint(&mut self) -> Checkpoint {
+        self.push_trivia();
+        Checkpoint(self.children.len())
+    }
+
+    // Starts a node that contains everything added since the checkpoint.
+    pub(crate) fn start_node_at(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        self.open_nodes.push((kind, checkpoint.0));
+    }
+
+    pub(crate) fn finish_node(&mut self) {
+        let (kind, start) = self.open_nodes.pop().expect("a node is open");
+        let children = self.children.drain(start..).collect();
+
+        self.children
+            .push(SyntaxElement::Node(SyntaxNode::new(kind, children)));
+    }
+
+    // Parses with `f` inside of a node. If `f` fails the node is left open,
+    // so that the input that couldn't be parsed ends up inside of it once the
+    // tree is finished.
+    pub(crate) fn node<T>(
+        &mut self,
+        kind: SyntaxKind,
+        f: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.start_node(kind);
+        let parsed = f(self)?;
+        self.finish_node();
+
+        Ok(parsed)
+    }
+
+    // Completes the syntax tree. Any tokens that weren't parsed are put in an
+    // error node, so that the tree still adds up to the input.
+    pub(crate) fn finish(mut self) -> (SyntaxNode, Vec<SyntaxError>) {
+        if let Some(last) = (self.pos..self.tokens.len())
+            .rev()
+            .find(|&idx| !self.tokens[idx].kind.is_trivia())
+        {
+            self.push_trivia();
+            self.start_node(SyntaxKind::Error);
+            self.push_tokens(last + 1);
+            self.finish_node();
+        }
+        self.push_tokens(self.tokens.len());
+
+        while !self.open_nodes.is_empty() {
+            self.finish_node();
+        }
+
+        // Wraps the elements in a root node, unless they already have one.
+        let mut children = self.children;
+        let root = match (children.pop(), children.is_empty()) {
+            (Some(SyntaxElement::Node(node)), true) => node,
+            (last, _) => {
+                children.extend(last);
+                SyntaxNode::new(SyntaxKind::Program, children)
+            }
+        };
+
+        (root, self.errors)
+    }
+
+    pub(crate) fn eat_punct(&mut self, punct: &str) -> bool {
+        let at_punct = self.at_punct(punct);
+        if at_punct {
+            self.bump();
+        }
+
+        at_punct
+    }
+
+    pub(crate) fn expect_punct(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", punct)))
+        }
+    }
+
+    pub(crate) fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let at_keyword = self.at_keyword(keyword);
+        if at_keyword {
+            self.bump();
+        }
+
+        at_keyword
+    }
+
+    pub(crate) fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", keyword)))
+        }
+    }
+
+    pub(crate) fn expect_ident(&mut self) -> Result<&'a str, String> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Ident => Ok(self.bump().text),
+            Some(token) if token.kind == TokenKind::Keyword => Err(format!(
+                "keyword '{}' cannot be used as an identifier",
+                token.text
+            )),
+            _ => Err(self.expected("identifier")),
+        }
+    }
+
+    // Errors from the lexer take precedence, since they explain why the
+    // expected token isn't there. Otherwise the error mentions the token
+    // before, as long as it's part of the same statement.
+    pub(crate) fn expected(&self, what: &str) -> String {
+        let prev = self.tokens[self.stmt_start..self.pos]
+            .iter()
+            .rev()
+            .find(|token| !token.kind.is_trivia());
+
+        match (self.peek(), prev) {
+            (Some(token), _) if token.kind == TokenKind::Error => token.error_message(),
+            (_, Some(prev)) => format!("expected {} after `{}`", what, prev.text),
+            (_, None) => format!("expected {}", what),
+        }
+    }
+
+    // Parses a statement with `f`. If that fails, the error is recorded and
+    // the rest of the statement is skipped, so that parsing can carry on
+    // with the next one.
+    pub(crate) fn recover<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Option<T> {
+        self.push_trivia();
+        let depth = self.open_nodes.len();
+        let start = mem::replace(&mut self.stmt_start, self.pos);
+
+        let parsed = match f(self) {
+            Ok(parsed) => Some(parsed),
+            Err(message) => {
+                let made_progress = self.pos > self.stmt_start;
+                self.error(message);
+                self.skip_stmt(made_progress, depth);
+
+                while self.open_nodes.len() > depth {
+                    self.finish_node();
+                }
+
+                None
+            }
+        };
+
+        self.stmt_start = start;
+        parsed
+    }
+
+    pub(crate) fn error(&mut self, message: String) {
+        let span = match self.peek() {
+            Some(token) => token.span.clone(),
+            None => self.source.len()..self.source.len(),
+        };
+
+        self.errors.push(SyntaxError::new(message, span));
+    }
+
+    // Skips to the end of the line, or to the `}` that closes the enclosing
+    // block, passing over anything in braces. The skipped tokens go into an
+    // error node. Errors from the lexer in there are reported as well.
+    fn skip_stmt(&mut self, made_progress: bool, depth: usize) {
+        let in_block = self.open_nodes[..depth]
+            .iter()
+            .any(|(kind, _)| *kind == SyntaxKind::Block);
+        let mut braces = 0;
+        let mut skipped = false;
+
+        while let Some(token) = self.peek() {
+            let at_open_brace = token.is(TokenKind::Punct, "{");
+            let at_close_brace = token.is(TokenKind::Punct, "}");
+            let lexer_error = match token.kind {
+                TokenKind::Error if skipped => Some(token.error_message()),
+                _ => None,
+            };
+
+            let at_boundary = self.newline_before(0) || (at_close_brace && in_block);
+            if braces == 0 && at_boundary && (made_progress || skipped) {
+                break;
+            }
+
+            if at_open_brace {
+                braces += 1;
+            } else if at_close_brace && braces > 0 {
+                braces -= 1;
+            }
+            if let Some(message) = lexer_error {
+                self.error(message);
+            }
+
+            if !skipped {
+                self.start_node(SyntaxKind::Error);
+                skipped = true;
+            }
+            self.bump();
+        }
+
+        if skipped {
+            self.finish_node();
+        }
+    }
+
+    // Moves past whitespace and comments at the end of the input.
+    pub(crate) fn skip_trailing_trivia(&mut self) {
+        if self.at_end() {
+            self.push_tokens(self.tokens.len());
+            self.offset = self.source.len();
+        }
+    }
+
+    pub(crate) fn remainder(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Program;
+
+    fn tree(node: &SyntaxNode) -> String {
+        let children: Vec<_> = node
+            .children()
+            .iter()
+            .map(|child| match child {
+                SyntaxElement::Node(node) => tree(node),
+                SyntaxElement::Token(token) => format!("{:?}", token.text()),
+            })
+            .collect();
+
+        format!("{:?}({})", node.kind(), children.join(" "))
+    }
+
+    fn parse_tree(s: &str) -> String {
+        let mut p = Parser::new(s);
+        let _ = Program::parse(&mut p);
+
+        tree(&p.finish().0)
+    }
+
+    #[test]
+    fn peek_skips_trivia() {
+        let p = Parser::new(" /* c */ x");
+        assert_eq!(p.peek().map(|token| token.text), Some("x"));
+        assert!(!p.is_adjacent());
+    }
+
+    #[test]
+    fn bump_leaves_trivia_in_remainder() {
+        let mut p = Parser::new("x  y");
+        assert_eq!(p.bump().text, "x");
+        assert_eq!(p.remainder(), "  y");
+    }
+
+    #[test]
+    fn newline_before_token() {
+        let p = Parser::new("a # c\nb /* \n */ c");
+        assert!(!p.newline_before(0));
+        assert!(p.newline_before(1));
+        assert!(!p.newline_before(2));
+        assert!(p.newline_before(3));
+    }
+
+    #[test]
+    fn expect_ident_rejects_keyword() {
+        let mut p = Parser::new("let");
+        assert_eq!(
+            p.expect_ident(),
+            Err("keyword 'let' cannot be used as an identifier".to_owned())
+        );
+    }
+
+    #[test]
+    fn expected_reports_lexer_error() {
+        let mut p = Parser::new("/* x");
+        assert_eq!(
+            p.expect_punct("("),
+            Err("unterminated block comment".to_owned())
+        );
+    }
+
+    #[test]
+    fn build_tree_with_trivia_outside_of_nodes() {
+        assert_eq!(
+            parse_tree(" -1 + f x? # c\n"),
+            r##"Program(" " Operation(Number("-" "1") " " "+" " " Propagate(FuncCall("f" " " BindingUsage("x")) "?")) " " "# c" "\n")"##
+        );
+    }
+
+    #[test]
+    fn build_tree_of_nested_operations() {
+        assert_eq!(
+            parse_tree("1*2+3"),
+            r#"Program(Operation(Operation(Number("1") "*" Number("2")) "+" Number("3")))"#
+        );
+    }
+
+    #[test]
+    fn build_tree_with_unparsed_input_in_error_node() {
+        assert_eq!(
+            parse_tree("let x = 1 + € 2 "),
+            r#"Program(BindingDef("let" " " "x" " " "=" " " Operation(Number("1") " " "+" " " Error("€" " " "2"))) " ")"#
+        );
+    }
+}
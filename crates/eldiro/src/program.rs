@@ -0,0 +1,262 @@
+use crate::env::Env;
+use crate::parser::{Parsable, Parser};
+use crate::stmt::Stmt;
+use crate::syntax::SyntaxKind;
+use crate::unwind::Unwind;
+use crate::val::Val;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvalError;
+    use crate::limits::Limits;
+    use crate::val;
+
+    fn eval(s: &str) -> Result<Val, Unwind> {
+        let (_, program) = Program::new(s).unwrap();
+        program.eval(&mut Env::default())
+    }
+
+    #[test]
+    fn parse_empty_program() {
+        assert_eq!(Program::new("  \n "), Ok(("", Program { stmts: vec![] })));
+    }
+
+    #[test]
+    fn parse_program_with_invalid_stmt() {
+        assert!(Program::new("let x = 1\nlet = 2").is_err());
+    }
+
+    #[test]
+    fn eval_program_with_tabs_and_crlf() {
+        assert_eq!(
+            eval("fn add a b =>\ta + b\r\nlet x = {\r\n\tadd 1 2\r\n}\r\nx\r\n"),
+            Ok(Val::Number(3))
+        );
+    }
+
+    #[test]
+    fn newline_ends_left_operand() {
+        assert_eq!(eval("let x = 1\n-1"), Ok(Val::Number(-1)));
+        assert_eq!(eval("let x = 1\n(2)\nx"), Ok(Val::Number(1)));
+    }
+
+    #[test]
+    fn newline_after_operator_continues_expr() {
+        assert_eq!(eval("let x = 1 +\n\t2\nx"), Ok(Val::Number(3)));
+    }
+
+    #[test]
+    fn pipe_continues_on_next_line() {
+        assert_eq!(
+            eval("fn double n => n * 2\n2\n  |> double\n  |> double"),
+            Ok(Val::Number(8))
+        );
+    }
+
+    #[test]
+    fn idents_starting_with_keywords() {
+        assert_eq!(
+            eval(
+                "let mut letter = 1
+letter = 2
+fn fname x => x
+let iffy = fname letter
+let whilst = iffy
+let format = [whilst]
+let mut breakfast = 0
+for input in format { breakfast = input }
+let returned = breakfast
+let tryhard = returned
+let Okay = tryhard
+let Error = Okay
+let elsewhere = Error
+let catchy = elsewhere
+let continued = catchy
+let mutable = continued
+let infix = mutable
+infix"
+            ),
+            Ok(Val::Number(2))
+        );
+    }
+
+    #[test]
+    fn eval_program_evaluates_to_last_stmt() {
+        assert_eq!(eval("let x = 2\nlet y = x * 3\ny + 1"), Ok(Val::Number(7)));
+    }
+
+    #[test]
+    fn eval_call_to_func_defined_later() {
+        assert_eq!(
+            eval("let x = double 2\nfn double n => n * 2\nx"),
+            Ok(Val::Number(4))
+        );
+    }
+
+    #[test]
+    fn eval_mutually_recursive_funcs() {
+        assert_eq!(
+            eval(
+                "fn even n => if n { odd (n - 1) } else { 1 }
+fn odd n => if n { even (n - 1) } else { 0 }
+even 10"
+            ),
+            Ok(Val::Number(1))
+        );
+    }
+
+    #[test]
+    fn eval_redefined_func_in_order() {
+        assert_eq!(
+            eval("fn f => 1\nlet x = f\nfn f => 2\nlet y = f\n[x, y]"),
+            Ok(Val::List(vec![Val::Number(1), Val::Number(2)].into()))
+        );
+    }
+
+    #[test]
+    fn func_does_not_see_bindings_of_caller() {
+        assert_eq!(
+            eval("fn get => y\n{ let y = 1\n get }"),
+            Err(Unwind::Error(EvalError::BindingDoesNotExist(
+                "y".to_owned()
+            )))
+        );
+    }
+
+    #[test]
+    fn local_func_calls_itself() {
+        assert_eq!(
+            eval("{ fn fact n => if n { n * fact (n - 1) } else { 1 }\n fact 5 }"),
+            Ok(Val::Number(120))
+        );
+    }
+
+    #[test]
+    fn binding_cannot_reference_itself() {
+        assert_eq!(
+            eval("let x = x"),
+            Err(Unwind::Error(EvalError::BindingDoesNotExist(
+                "x".to_owned()
+            )))
+        );
+    }
+
+    fn eval_with_limits(s: &str, limits: Limits) -> Result<Val, Unwind> {
+        let (_, program) = Program::new(s).unwrap();
+        program.eval(&mut Env::with_limits(limits))
+    }
+
+    #[test]
+    fn lists_not_bound_to_anything_count_towards_heap_limit() {
+        // Each call to `d` makes a list that's charged as if it copied `y`
+        // thirty times, so two of them are over the limit.
+        let s = format!(
+            "let y = [{}]\nfn d => [{}]\n[{}]",
+            vec!["0"; 1000].join(", "),
+            vec!["y"; 30].join(", "),
+            vec!["d"; 300].join(", ")
+        );
+
+        assert_eq!(
+            eval_with_limits(
+                &s,
+                Limits {
+                    max_heap_bytes: Some(1_000_000),
+                    ..Limits::default()
+                }
+            ),
+            Err(Unwind::Error(EvalError::HeapLimitExceeded {
+                max_heap_bytes: 1_000_000
+            }))
+        );
+    }
+
+    #[test]
+    fn dropped_lists_no_longer_count_towards_heap_limit() {
+        assert_eq!(
+            eval_with_limits(
+                "let mut x = 0\nfor i in 0..1000 { x = [i, i, i, i] }\nx",
+                Limits {
+                    max_heap_bytes: Some(1_000),
+                    ..Limits::default()
+                }
+            ),
+            Ok(Val::List(vec![Val::Number(999); 4].into()))
+        );
+    }
+
+    #[test]
+    fn cannot_nest_values_too_deeply() {
+        // Dropping the value recurses through it, so this would overflow the
+        // stack if the nesting weren't limited.
+        let result = std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(|| {
+                eval("let mut x = 0\nfor i in 0..60000 { x = [x] }\nx")
+                    .map(|val| val.to_string())
+                    .map_err(|unwind| unwind.into_error().to_string())
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            result,
+            Err(EvalError::ValTooDeep {
+                max_depth: val::MAX_DEPTH
+            }
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn cannot_nest_results_too_deeply() {
+        assert_eq!(
+            eval("let mut x = 0\nfor i in 0..60000 { x = Ok [Err x] }\nx"),
+            Err(Unwind::Error(EvalError::ValTooDeep {
+                max_depth: val::MAX_DEPTH
+            }))
+        );
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub(crate) struct Program {
+    pub(crate) stmts: Vec<Stmt>,
+}
+
+impl Parsable for Program {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        p.node(SyntaxKind::Program, |p| {
+            let mut stmts = Vec::new();
+            while !p.at_end() {
+                stmts.extend(p.recover(Stmt::parse));
+            }
+
+            p.skip_trailing_trivia();
+
+            Ok(Self { stmts })
+        })
+    }
+}
+
+impl Program {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Unwind> {
+        // Functions are defined up front so that they can call functions
+        // defined further down. Definitions are evaluated again in order
+        // below, so redefining a function only takes effect from that point.
+        for stmt in &self.stmts {
+            if let Stmt::FuncDef(func_def) = stmt {
+                func_def.eval(env)?;
+            }
+        }
+
+        let mut result = Val::Unit;
+        for stmt in &self.stmts {
+            result = stmt.eval(env)?;
+        }
+
+        Ok(result)
+    }
+}
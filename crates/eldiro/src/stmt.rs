@@ -1,11 +1,14 @@
-use crate::expr::{BindingUsage, Expr};
+use crate::expr::{Expr, Tail};
 use crate::func_def::FuncDef;
+use crate::parser::{Parsable, Parser};
+use crate::unwind::Unwind;
+use crate::utils;
 use crate::{binding_def::BindingDef, env::Env, val::Val};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::expr::{Number, Op};
+    use crate::expr::{BindingUsage, Number, Op};
     #[test]
     fn parse_binding_def() {
         assert_eq!(
@@ -15,6 +18,7 @@ mod tests {
                 Stmt::BindingDef(BindingDef {
                     name: "x".to_owned(),
                     val: Expr::Number(Number(3)),
+                    mutable: false,
                 })
             ))
         );
@@ -73,17 +77,29 @@ pub(crate) enum Stmt {
     FuncDef(FuncDef),
 }
 
+impl Parsable for Stmt {
+    fn parse(p: &mut Parser) -> Result<Self, String> {
+        utils::nested(|| {
+            if p.at_keyword("fn") {
+                FuncDef::parse(p).map(Self::FuncDef)
+            } else if p.at_keyword("let") {
+                BindingDef::parse(p).map(Self::BindingDef)
+            } else {
+                Expr::parse(p).map(Self::Expr)
+            }
+        })
+    }
+}
+
 impl Stmt {
-    pub fn new(s: &str) -> Result<(&str, Self), String> {
-        FuncDef::new(s)
-            .map(|(s, func_def)| (s, Self::FuncDef(func_def)))
-            .or_else(|_| {
-                BindingDef::new(s).map(|(s, binding_def)| (s, Self::BindingDef(binding_def)))
-            })
-            .or_else(|_| Expr::new(s).map(|(s, expr)| (s, Self::Expr(expr))))
+    pub(crate) fn eval_tail(&self, env: &mut Env) -> Result<Tail, Unwind> {
+        match self {
+            Stmt::Expr(expr) => expr.eval_tail(env),
+            _ => self.eval(env).map(Tail::Val),
+        }
     }
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, String> {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Unwind> {
         match self {
             Stmt::BindingDef(binding_def) => binding_def.eval(env).map(|_| Val::Unit),
             Stmt::Expr(expr) => expr.eval(env),
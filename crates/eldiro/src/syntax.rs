@@ -0,0 +1,174 @@
+use crate::lexer::TokenKind;
+use std::fmt;
+use std::ops::Range;
+
+/// The kind of a node in the syntax tree.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SyntaxKind {
+    Program,
+    BindingDef,
+    FuncDef,
+    Assignment,
+    Number,
+    BindingUsage,
+    Operation,
+    Unary,
+    Range,
+    Pipe,
+    FuncCall,
+    Paren,
+    List,
+    Block,
+    If,
+    While,
+    For,
+    Break,
+    Continue,
+    Return,
+    Ok,
+    Err,
+    Propagate,
+    TryCatch,
+    Catch,
+    /// Input the parser couldn't make sense of.
+    Error,
+}
+
+/// A node of the lossless syntax tree. The tokens below a node, including
+/// whitespace and comments, add up to the source text it was parsed from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxToken {
+    kind: TokenKind,
+    text: String,
+    span: Range<usize>,
+}
+
+impl SyntaxNode {
+    pub(crate) fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> Self {
+        Self { kind, children }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    pub fn child_tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(_) => None,
+        })
+    }
+
+    /// All tokens below this node, in source order.
+    pub fn descendant_tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// The byte range of the source text this node was parsed from.
+    pub fn span(&self) -> Range<usize> {
+        let tokens = self.descendant_tokens();
+
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.start..last.span.end,
+            _ => 0..0,
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.descendant_tokens()
+            .into_iter()
+            .try_for_each(|token| write!(f, "{}", token))
+    }
+}
+
+impl SyntaxToken {
+    pub(crate) fn new(kind: TokenKind, text: &str, span: Range<usize>) -> Self {
+        Self {
+            kind,
+            text: text.to_owned(),
+            span,
+        }
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// A syntax error, found at the token `span` points to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxError {
+    message: String,
+    span: Range<usize>,
+}
+
+impl SyntaxError {
+    pub(crate) fn new(message: String, span: Range<usize>) -> Self {
+        Self { message, span }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
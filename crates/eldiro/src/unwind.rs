@@ -0,0 +1,29 @@
+use crate::error::EvalError;
+use crate::val::Val;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Unwind {
+    Break(Val),
+    Continue,
+    Return(Val),
+    Propagate(Val),
+    Error(EvalError),
+}
+
+impl Unwind {
+    pub(crate) fn into_error(self) -> EvalError {
+        match self {
+            Self::Break(_) => EvalError::BreakOutsideLoop,
+            Self::Continue => EvalError::ContinueOutsideLoop,
+            Self::Return(_) => EvalError::ReturnOutsideFunc,
+            Self::Propagate(_) => EvalError::PropagateOutsideFunc,
+            Self::Error(error) => error,
+        }
+    }
+}
+
+impl From<EvalError> for Unwind {
+    fn from(error: EvalError) -> Self {
+        Self::Error(error)
+    }
+}
@@ -1,16 +1,270 @@
+use crate::env::Env;
+use crate::error::EvalError;
+use crate::limits::HeapCharge;
+use crate::stmt::Stmt;
 use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+// How deeply values may be nested. Dropping, comparing and printing a value
+// recurse through it, and printing takes up to a kilobyte of stack per level
+// in debug builds, so this keeps them well within the stack left over once
+// evaluation hits its own stack limit.
+pub(crate) const MAX_DEPTH: usize = 256;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Val {
     Number(i32),
+    List(List),
+    Range { start: i32, end: i32 },
+    Ok(Box<Val>),
+    Err(Box<Val>),
+    Error(Box<EvalError>),
+    Func(Box<Func>),
     Unit,
 }
 
+/// The items of a list. Values can't be changed, so copies of a list share
+/// its items instead of copying them.
+#[derive(Clone)]
+pub struct List(Rc<ListData>);
+
+struct ListData {
+    items: Vec<Val>,
+    // Both are kept so that making a list out of other lists doesn't have to
+    // walk through their items.
+    depth: usize,
+    heap_size: usize,
+    // Lists made during evaluation count towards the heap limit for as long
+    // as any copy of them is around.
+    _charge: Option<HeapCharge>,
+}
+
+// A function that has been given fewer arguments than it has parameters.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Func {
+    pub(crate) name: String,
+    pub(crate) params: Rc<[String]>,
+    pub(crate) body: Rc<Stmt>,
+    // The environment the function was defined in.
+    pub(crate) env_id: usize,
+    pub(crate) args: List,
+}
+
+impl Val {
+    pub(crate) fn is_truthy(&self) -> Result<bool, EvalError> {
+        match self {
+            Self::Number(n) => Ok(*n != 0),
+            _ => Err(EvalError::NonNumberCondition(self.clone())),
+        }
+    }
+
+    // Wraps the value in an `Ok` or `Err`, as long as that doesn't nest it
+    // too deeply.
+    pub(crate) fn wrap(self, wrap: fn(Box<Self>) -> Self) -> Result<Self, EvalError> {
+        wrap(Box::new(self)).checked()
+    }
+
+    // Fails if the value is nested too deeply.
+    pub(crate) fn checked(self) -> Result<Self, EvalError> {
+        check_depth(self.depth())?;
+        Ok(self)
+    }
+
+    // How many values deep this value is nested.
+    pub(crate) fn depth(&self) -> usize {
+        match self {
+            Self::List(list) => list.0.depth,
+            Self::Ok(val) | Self::Err(val) => 1 + val.depth(),
+            Self::Error(error) => 1 + error.val().map_or(0, Self::depth),
+            Self::Func(func) => 1 + func.args.0.depth,
+            Self::Number(_) | Self::Range { .. } | Self::Unit => 0,
+        }
+    }
+
+    // An estimate of how many bytes this value owns outside of itself,
+    // counting the items of the lists it holds as if they weren't shared.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.heap_size_with(&|list| list.0.heap_size)
+    }
+
+    // Like `heap_size`, but leaving out lists, which count towards the heap
+    // limit by themselves.
+    pub(crate) fn unshared_heap_size(&self) -> usize {
+        self.heap_size_with(&|_| 0)
+    }
+
+    fn heap_size_with(&self, list_size: &impl Fn(&List) -> usize) -> usize {
+        match self {
+            Self::List(list) => list_size(list),
+            Self::Ok(val) | Self::Err(val) => {
+                mem::size_of::<Self>() + val.heap_size_with(list_size)
+            }
+            Self::Error(error) => {
+                mem::size_of::<EvalError>()
+                    + error.val().map_or(0, |val| {
+                        mem::size_of::<Self>() + val.heap_size_with(list_size)
+                    })
+            }
+            Self::Func(func) => mem::size_of::<Func>() + list_size(&func.args),
+            Self::Number(_) | Self::Range { .. } | Self::Unit => 0,
+        }
+    }
+
+    // Whether this value is or holds a function for which `f` is true.
+    pub(crate) fn any_func(&self, f: &impl Fn(&Func) -> bool) -> bool {
+        match self {
+            Self::List(list) => list.items().iter().any(|item| item.any_func(f)),
+            Self::Ok(val) | Self::Err(val) => val.any_func(f),
+            Self::Func(func) => f(func) || func.args.items().iter().any(|arg| arg.any_func(f)),
+            Self::Number(_) | Self::Range { .. } | Self::Error(_) | Self::Unit => false,
+        }
+    }
+
+    pub(crate) fn into_items(self) -> Result<Box<dyn Iterator<Item = Val>>, EvalError> {
+        match self {
+            Self::List(list) => Ok(Box::new(
+                (0..list.items().len()).map(move |idx| list.items()[idx].clone()),
+            )),
+            Self::Range { start, end } => Ok(Box::new((start..end).map(Val::Number))),
+            _ => Err(EvalError::NotIterable(self)),
+        }
+    }
+}
+
+fn check_depth(depth: usize) -> Result<(), EvalError> {
+    if depth > MAX_DEPTH {
+        return Err(EvalError::ValTooDeep {
+            max_depth: MAX_DEPTH,
+        });
+    }
+
+    Ok(())
+}
+
+impl List {
+    // Makes a list during evaluation, which counts towards the heap limit.
+    pub(crate) fn new(items: Vec<Val>, env: &Env) -> Result<Self, EvalError> {
+        let (depth, heap_size) = Self::measure(&items);
+        check_depth(depth)?;
+        let charge = env.charge_heap(heap_size)?;
+
+        Ok(Self(Rc::new(ListData {
+            items,
+            depth,
+            heap_size,
+            _charge: Some(charge),
+        })))
+    }
+
+    fn measure(items: &[Val]) -> (usize, usize) {
+        let depth = 1 + items.iter().map(Val::depth).max().unwrap_or(0);
+        let heap_size = items
+            .iter()
+            .map(|item| mem::size_of::<Val>() + item.heap_size())
+            .sum();
+
+        (depth, heap_size)
+    }
+
+    pub fn items(&self) -> &[Val] {
+        &self.0.items
+    }
+}
+
+impl From<Vec<Val>> for List {
+    fn from(items: Vec<Val>) -> Self {
+        let (depth, heap_size) = Self::measure(&items);
+
+        Self(Rc::new(ListData {
+            items,
+            depth,
+            heap_size,
+            _charge: None,
+        }))
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.items() == other.items()
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.items()).finish()
+    }
+}
+
 impl fmt::Display for Val {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Number(n) => write!(f, "{}", n),
+            Self::List(list) => {
+                write!(f, "[")?;
+                for (idx, item) in list.items().iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Self::Range { start, end } => write!(f, "{}..{}", start, end),
+            Self::Ok(val) => write!(f, "Ok({})", val),
+            Self::Err(val) => write!(f, "Err({})", val),
+            Self::Error(error) => write!(f, "Error({})", error),
+            Self::Func(func) => write!(f, "Func({})", func.name),
             Self::Unit => write!(f, "Unit"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_list() {
+        assert_eq!(
+            Val::List(vec![Val::Number(1), Val::Unit, Val::List(vec![].into())].into()).to_string(),
+            "[1, Unit, []]"
+        );
+    }
+
+    #[test]
+    fn display_results() {
+        assert_eq!(Val::Ok(Box::new(Val::Number(1))).to_string(), "Ok(1)");
+        assert_eq!(
+            Val::Err(Box::new(Val::Error(Box::new(EvalError::BreakOutsideLoop)))).to_string(),
+            "Err(Error(cannot use 'break' outside of a loop))"
+        );
+    }
+
+    #[test]
+    fn iterate_over_range() {
+        assert_eq!(
+            Val::Range { start: 1, end: 4 }
+                .into_items()
+                .map(|items| items.collect::<Vec<_>>()),
+            Ok(vec![Val::Number(1), Val::Number(2), Val::Number(3)])
+        );
+    }
+
+    #[test]
+    fn heap_size_of_nested_list() {
+        let val_size = mem::size_of::<Val>();
+
+        assert_eq!(Val::Number(1).heap_size(), 0);
+        assert_eq!(
+            Val::List(vec![Val::Number(1), Val::List(vec![Val::Unit].into())].into()).heap_size(),
+            3 * val_size
+        );
+    }
+
+    #[test]
+    fn cannot_iterate_over_number() {
+        assert!(Val::Number(3).into_items().is_err());
+    }
+}